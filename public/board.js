@@ -17,6 +17,8 @@ export class Board {
         this.internal_state = initial_state
         this.moves_map = moves_map
         this.to_move = to_move
+        // The moves of |moves_map| that lose material, in the same format.
+        this.unsafe_moves_map = null
     }
 
     setPiece(row, col, piece) {
@@ -43,6 +45,14 @@ export class Board {
         return this.moves_map;
     }
 
+    setUnsafeMovesMap(unsafe_moves_map) {
+        this.unsafe_moves_map = unsafe_moves_map;
+    }
+
+    unsafeMovesMap() {
+        return this.unsafe_moves_map;
+    }
+
     hasMovesMap() {
         return this.movesMap != null;
     }
@@ -52,7 +62,9 @@ export class Board {
         return WHITE_PLAYER
     }
     copy() {
-        return new Board(this.internal_state, this.moves_map, this.to_move);
+        let board = new Board(this.internal_state, this.moves_map, this.to_move);
+        board.setUnsafeMovesMap(this.unsafe_moves_map);
+        return board;
     }
 
     copy_as_continuation() {
@@ -1,5 +1,5 @@
 import { Board } from "./board.js"
-import { IssuePossibleMovesReq, parsePossibleMoves, IssuePositionEvalReq } from "./client.js";
+import { IssuePossibleMovesReq, IssueMoveSafetyReq, parsePossibleMoves, IssuePositionEvalReq } from "./client.js";
 export class BoardStateManager {
   constructor(initialBoardState = new Board()) {
     this.boardState = []
@@ -8,6 +8,7 @@ export class BoardStateManager {
 
   push_state(new_position) {
     new_position.setMoveMap(parsePossibleMoves(IssuePossibleMovesReq(new_position, new_position.to_move)));
+    new_position.setUnsafeMovesMap(parsePossibleMoves(IssueMoveSafetyReq(new_position, new_position.to_move)));
     let pos_score = IssuePositionEvalReq(new_position, new_position.to_move);
     console.log('New position scoore:');
     console.log(pos_score);
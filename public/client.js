@@ -4,10 +4,12 @@ export const kPromotionMoveMapEntry = 'promotion';
 
 
 class UciRequest {
-    constructor(board, req_type) {
+    constructor(board, req_type, multi_pv = 0) {
         this.board = EncodeBoard(board);
         this.req_type = req_type;
         this.timeout = 3000;
+        // Number of lines of a "multi_pv" or "analysis" request. Other requests leave it at 0.
+        this.multi_pv = multi_pv;
     }
 }
 
@@ -72,7 +74,8 @@ export function GetShortClastingSquareforKing(from) {
 
 export async function parsePossibleMoves(possibleMoves) {
     let temp = await possibleMoves;
-    const moves = temp.split(',');
+    // No moves at all, e.g. when none of them is unsafe.
+    const moves = temp == "" ? [] : temp.split(',');
     const moveMap = new Map();
 
     for (const move of moves) {
@@ -113,6 +116,18 @@ export async function IssuePossibleMovesReq(board, player_to_move) {
     return uci_response.possible_moves;
 }
 
+// Moves of |player_to_move| that lose material, in the format of IssuePossibleMovesReq.
+export async function IssueMoveSafetyReq(board, player_to_move) {
+    let uci_req = {
+        p_to_move: player_to_move,
+        board: EncodeBoard(board),
+        req_type: "move_safety",
+        timeout: 3000,
+    }
+    let uci_response = await IssueUciReq(uci_req);
+    return uci_response.possible_moves;
+}
+
 export async function IssuePositionEvalReq(board, player_to_move) {
     let uci_req = {
         p_to_move: player_to_move,
@@ -124,6 +139,56 @@ export async function IssuePositionEvalReq(board, player_to_move) {
     return uci_response.pos_score;
 }
 
+// The |num_lines| best moves of |player_to_move|, best first, each as
+// {move, score, pv, depth}.
+export async function IssueMultiPvReq(board, player_to_move, num_lines) {
+    let uci_req = {
+        p_to_move: player_to_move,
+        board: EncodeBoard(board),
+        req_type: "multi_pv",
+        timeout: 3000,
+        multi_pv: num_lines,
+    }
+    let uci_response = await IssueUciReq(uci_req);
+    return uci_response.lines;
+}
+
+// Starts analyzing the position in the background, with the |num_lines| best moves. Returns
+// {session_id, running, lines}, to be passed to IssueAnalysisPollReq and IssueAnalysisStopReq.
+export async function IssueAnalysisStartReq(board, player_to_move, num_lines) {
+    let uci_req = {
+        p_to_move: player_to_move,
+        board: EncodeBoard(board),
+        req_type: "analysis",
+        timeout: 0,
+        multi_pv: num_lines,
+    }
+    return await IssueAnalysisReq("start", "POST", uci_req);
+}
+
+// The lines found so far, in the format of IssueMultiPvReq.
+export async function IssueAnalysisPollReq(session_id) {
+    return await IssueAnalysisReq(session_id, "GET");
+}
+
+export async function IssueAnalysisStopReq(session_id) {
+    return await IssueAnalysisReq(session_id + "/stop", "POST");
+}
+
+async function IssueAnalysisReq(path, method, body) {
+    const url = "http://127.0.0.1:9999/analysis/" + path;
+    let response = await fetch(url, {
+        method: method,
+        mode: "cors",
+        body: body === undefined ? undefined : JSON.stringify(body),
+        headers: {
+            "Content-Type": "application/json",
+            "Access-Control-Allow-Origin": "*",
+        }
+    });
+    return await (response.json());
+}
+
 async function IssueUciReq(uci_req) {
     const url = "http://127.0.0.1:9999";
     console.log('Sending request to Server..');
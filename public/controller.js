@@ -179,10 +179,12 @@ export class Controller {
         // console.log(moves);
         let moves = movesMap.get(square_as_str);
         let promotions_set = movesMap.get(kPromotionMoveMapEntry);
+        // Moves that lose material are told apart, as a hint before playing them.
+        let unsafe_moves = (await this.getCurPosition().unsafeMovesMap()).get(square_as_str) ?? [];
         if (typeof moves !== "undefined") {
             moves.forEach((sq) => {
                 this.selected_square_moves.add(sq);
-                this.drawOverlay(sq);
+                this.drawOverlay(sq, unsafe_moves.includes(sq));
                 if (promotions_set.has(sq)) {
                     this.promotion_info.possible_promotions.add(sq);
                 }
@@ -206,7 +208,7 @@ export class Controller {
         if (typeof moves !== "undefined") {
             moves.forEach((sq) => {
                 let sqElem = document.getElementById(sq + OVERLAY_DIV_SUFFIX);
-                sqElem.classList.remove('red-overlay');
+                sqElem.classList.remove('red-overlay', 'unsafe-overlay');
             });
         }
         this.promotion_info.possible_promotions.clear();
@@ -290,9 +292,10 @@ export class Controller {
         piece_div.classList.add(this.piece_to_class.get(pos.getPiece(i, j)));
     }
 
-    drawOverlay(toName) {
+    drawOverlay(toName, is_unsafe = false) {
         /** Draws a red circle on the square with the given name.
-         * @param {string} toName The name of the square to `draw` the circle on. */
+         * @param {string} toName The name of the square to `draw` the circle on.
+         * @param {boolean} is_unsafe Whether moving there loses material. */
         console.log(toName);
         let sqElem = document.getElementById(toName + OVERLAY_DIV_SUFFIX);
         if (sqElem == undefined) {
@@ -300,6 +303,9 @@ export class Controller {
             return;
         }
         sqElem.classList.add("red-overlay");
+        if (is_unsafe) {
+            sqElem.classList.add("unsafe-overlay");
+        }
     }
 
     removeAllChildElements(node_div) {
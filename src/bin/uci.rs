@@ -0,0 +1,20 @@
+// Runs the engine as a UCI engine over stdin/stdout, for chess GUIs and tournament managers.
+extern crate chess_gpt;
+
+use chess_gpt::uci::UciEngine;
+use std::io::{self, BufRead};
+
+fn main() {
+    let mut engine = UciEngine::new();
+    for line in io::stdin().lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        if !engine.handle_command(&line) {
+            return;
+        }
+    }
+    // The GUI closed our input without sending `quit`.
+    engine.handle_command("quit");
+}
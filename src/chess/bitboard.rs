@@ -9,6 +9,8 @@ pub const NUM_COLS: usize = 8;
 pub type BitB64 = u64;
 pub const EMPTY_BOARD: BitB64 = 0u64;
 pub const FULL_BOARD: BitB64 = u64::MAX;
+// a1, c1, ..., b2, d2, ...
+pub const DARK_SQUARES: BitB64 = 0xAA55_AA55_AA55_AA55;
 
 pub const A1: u8 = 0u8;
 pub const B1: u8 = 1u8;
@@ -75,6 +77,26 @@ pub const F8: u8 = 61u8;
 pub const G8: u8 = 62u8;
 pub const H8: u8 = 63u8;
 
+// Square name in coordinate notation, e.g. 0 => "a1", 63 => "h8".
+pub fn sq_id_to_coordinates(sq_id: u8) -> String {
+    let file = (b'a' + sq_id % 8) as char;
+    let rank = (b'1' + sq_id / 8) as char;
+    format!("{}{}", file, rank)
+}
+
+// Inverse of sq_id_to_coordinates. Returns None for anything that is not a square name.
+pub fn sq_id_from_coordinates(name: &str) -> Option<u8> {
+    let bytes = name.as_bytes();
+    if bytes.len() != 2 {
+        return None;
+    }
+    let (file, rank) = (bytes[0], bytes[1]);
+    if !(b'a'..=b'h').contains(&file) || !(b'1'..=b'8').contains(&rank) {
+        return None;
+    }
+    Some((rank - b'1') * 8 + (file - b'a'))
+}
+
 pub trait BitArraySize {
     // The actual type.
     type Size;
@@ -166,7 +188,7 @@ impl BitArraySize for u64 {
 }
 
 // Bitboard with information about the pieces of one PlayerColor.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct PlayerBitboard {
     pub pawns: BitB64,
     pub knights: BitB64,
@@ -257,6 +279,25 @@ pub struct BitboardMove {
     pub sp_move_type: SpecialMoveType,
 }
 
+impl BitboardMove {
+    // Coordinate notation as used by UCI, e.g. "e2e4", "e1g1" or "e7e8q".
+    pub fn to_coordinates(&self) -> String {
+        let promotion = match self.sp_move_type {
+            SpecialMoveType::PromotionToKnight => "n",
+            SpecialMoveType::PromotionToBishop => "b",
+            SpecialMoveType::PromotionToRook => "r",
+            SpecialMoveType::PromotionToQueen => "q",
+            _ => "",
+        };
+        format!(
+            "{}{}{}",
+            sq_id_to_coordinates(self.from),
+            sq_id_to_coordinates(self.to),
+            promotion
+        )
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
 pub enum SpecialMoveType {
     RegularMove, // Move is not special,
@@ -269,3 +310,16 @@ pub enum SpecialMoveType {
     EnPassantLeft,
     EnPassantRight,
 }
+
+impl SpecialMoveType {
+    // The piece a pawn turns into, for promotions.
+    pub fn promotion_piece_type(&self) -> Option<PieceType> {
+        match self {
+            SpecialMoveType::PromotionToKnight => Some(PieceType::Knight),
+            SpecialMoveType::PromotionToBishop => Some(PieceType::Bishop),
+            SpecialMoveType::PromotionToRook => Some(PieceType::Rook),
+            SpecialMoveType::PromotionToQueen => Some(PieceType::Queen),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::chess::position::Position;
+
+// Zobrist hashes of the positions of a game, and of the search line on top of it, oldest
+// first. The last hash is the current position.
+#[derive(Clone, Default, Debug)]
+pub struct PositionHistory {
+    hashes: Vec<u64>,
+}
+
+impl PositionHistory {
+    pub fn new(position: &Position) -> Self {
+        PositionHistory {
+            hashes: vec![position.position_info.zobrist_hash],
+        }
+    }
+
+    pub fn push(&mut self, position: &Position) {
+        self.hashes.push(position.position_info.zobrist_hash);
+    }
+
+    pub fn pop(&mut self) {
+        self.hashes.pop();
+    }
+
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    // How many times the current position, |position|, occurred before. Only the positions
+    // since the last capture or pawn move can repeat, and only those with the same player to
+    // move, i.e. every other one.
+    pub fn repetitions(&self, position: &Position) -> usize {
+        let hash = position.position_info.zobrist_hash;
+        let reversible_plies = position.position_info.halfmove_clock as usize;
+        self.hashes
+            .iter()
+            .rev()
+            .take(reversible_plies + 1)
+            .skip(2)
+            .step_by(2)
+            .filter(|previous| **previous == hash)
+            .count()
+    }
+
+    // Threefold repetition, which either player may claim as a draw.
+    pub fn is_threefold_repetition(&self, position: &Position) -> bool {
+        self.repetitions(position) >= 2
+    }
+}
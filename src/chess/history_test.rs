@@ -0,0 +1,55 @@
+use super::history::PositionHistory;
+use super::position::Position;
+
+// Plays |moves| from |fen| and returns the final position along with its history.
+fn play(fen: &str, moves: &[&str]) -> (Position, PositionHistory) {
+    let mut position = Position::from_fen(fen).unwrap();
+    let mut history = PositionHistory::new(&position);
+    for coordinates in moves {
+        let mv = position.find_legal_move(coordinates).unwrap();
+        position = position.play_move(mv);
+        history.push(&position);
+    }
+    (position, history)
+}
+
+const KNIGHTS_SHUFFLE: [&str; 8] = [
+    "g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8",
+];
+
+#[test]
+fn test_repetitions() {
+    let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    let (position, history) = play(fen, &KNIGHTS_SHUFFLE[..3]);
+    assert_eq!(history.len(), 4);
+    assert_eq!(history.repetitions(&position), 0);
+
+    let (position, history) = play(fen, &KNIGHTS_SHUFFLE[..4]);
+    assert_eq!(history.repetitions(&position), 1);
+    assert!(!history.is_threefold_repetition(&position));
+
+    let (position, history) = play(fen, &KNIGHTS_SHUFFLE);
+    assert_eq!(history.repetitions(&position), 2);
+    assert!(history.is_threefold_repetition(&position));
+}
+
+#[test]
+fn test_repetitions_need_the_same_player_to_move() {
+    // The shuffle before the pawn move cannot repeat, the one after it can.
+    let (position, history) = play(
+        "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+        &[
+            "e1d1", "e8d8", "d1e1", "d8e8", "e2e3", "e8d8", "e1d1", "d8e8", "d1e1",
+        ],
+    );
+    assert_eq!(history.repetitions(&position), 1);
+
+    // Same pieces on the same squares, but not the same player to move.
+    let (position, history) = play("4k3/8/8/8/8/8/8/4K2R w - - 0 1", &["h1h2", "e8d8", "h2h1"]);
+    assert_eq!(history.repetitions(&position), 0);
+    let (position, history) = play(
+        "4k3/8/8/8/8/8/8/4K2R w - - 0 1",
+        &["h1h2", "e8d8", "h2h1", "d8e8"],
+    );
+    assert_eq!(history.repetitions(&position), 1);
+}
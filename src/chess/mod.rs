@@ -35,6 +35,48 @@ pub struct ChessPiece {
     pub color: PlayerColor,
 }
 
+impl ChessPiece {
+    // FEN letter: uppercase for white, lowercase for black.
+    pub fn from_fen_char(ch: char) -> Option<ChessPiece> {
+        let color = if ch.is_ascii_uppercase() {
+            PlayerColor::White
+        } else {
+            PlayerColor::Black
+        };
+        let typpe = match ch.to_ascii_lowercase() {
+            'p' => PieceType::Pawn,
+            'n' => PieceType::Knight,
+            'b' => PieceType::Bishop,
+            'r' => PieceType::Rook,
+            'q' => PieceType::Queen,
+            'k' => PieceType::King,
+            _ => return None,
+        };
+        Some(ChessPiece { typpe, color })
+    }
+
+    pub fn to_fen_char(&self) -> char {
+        let ch = match self.typpe {
+            PieceType::Pawn => 'p',
+            PieceType::Knight => 'n',
+            PieceType::Bishop => 'b',
+            PieceType::Rook => 'r',
+            PieceType::Queen => 'q',
+            PieceType::King => 'k',
+        };
+        match self.color {
+            PlayerColor::White => ch.to_ascii_uppercase(),
+            PlayerColor::Black => ch,
+        }
+    }
+}
+
 pub mod bitboard;
+pub mod history;
+pub mod perft;
 pub mod position;
 pub mod zobrist;
+
+mod history_test;
+mod perft_test;
+mod position_test;
@@ -0,0 +1,41 @@
+use super::position::{Position, UndoStack};
+
+impl Position {
+    // Number of leaf nodes in the legal move tree of the given depth.
+    pub fn perft(&self, depth: u8) -> u64 {
+        let mut position = *self;
+        position.perft_in_place(depth, &mut UndoStack::new())
+    }
+
+    // Same as perft(), making and unmaking the moves on this position instead of copying it.
+    fn perft_in_place(&mut self, depth: u8, undo_stack: &mut UndoStack) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let continuations = self.legal_continuations();
+        if depth == 1 {
+            return continuations.len() as u64;
+        }
+        let mut nodes = 0;
+        for mv in continuations.iter() {
+            self.make_move_in_place(*mv, undo_stack);
+            nodes += self.perft_in_place(depth - 1, undo_stack);
+            self.unmake_move(undo_stack);
+        }
+        nodes
+    }
+
+    // Perft split by root move, sorted by the move in coordinate notation. Useful to find the
+    // exact move where two move generators disagree.
+    pub fn divide(&self, depth: u8) -> Vec<(String, u64)> {
+        let mut result = Vec::new();
+        if depth == 0 {
+            return result;
+        }
+        for mv in self.legal_continuations().iter() {
+            result.push((mv.to_coordinates(), self.play_move(*mv).perft(depth - 1)));
+        }
+        result.sort();
+        result
+    }
+}
@@ -0,0 +1,75 @@
+use super::position::{Position, INITIAL_POSITION_FEN};
+
+// Reference positions and node counts from https://www.chessprogramming.org/Perft_Results.
+const KIWIPETE_FEN: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+const POSITION_3_FEN: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+const POSITION_4_FEN: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+const POSITION_4_MIRRORED_FEN: &str =
+    "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1";
+const POSITION_5_FEN: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+const POSITION_6_FEN: &str =
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
+fn assert_perft(fen: &str, expected_nodes: &[u64]) {
+    let pos = Position::from_fen(fen).unwrap();
+    for (depth, expected) in expected_nodes.iter().enumerate() {
+        assert_eq!(
+            pos.perft(depth as u8 + 1),
+            *expected,
+            "{} at depth {}",
+            fen,
+            depth + 1
+        );
+    }
+}
+
+#[test]
+fn test_perft_initial_position() {
+    assert_perft(INITIAL_POSITION_FEN, &[20, 400, 8902]);
+}
+
+#[test]
+fn test_perft_kiwipete() {
+    assert_perft(KIWIPETE_FEN, &[48, 2039]);
+}
+
+#[test]
+fn test_perft_position_3() {
+    assert_perft(POSITION_3_FEN, &[14, 191, 2812, 43238]);
+}
+
+#[test]
+fn test_perft_position_4() {
+    assert_perft(POSITION_4_FEN, &[6, 264, 9467]);
+    assert_perft(POSITION_4_MIRRORED_FEN, &[6, 264, 9467]);
+}
+
+#[test]
+fn test_perft_position_5() {
+    assert_perft(POSITION_5_FEN, &[44, 1486, 62379]);
+}
+
+#[test]
+fn test_perft_position_6() {
+    assert_perft(POSITION_6_FEN, &[46, 2079]);
+}
+
+#[test]
+fn test_divide_initial_position() {
+    let divide = Position::new().divide(2);
+    assert_eq!(divide.len(), 20);
+    assert_eq!(divide[0], ("a2a3".to_string(), 20));
+    assert_eq!(divide.iter().map(|(_, nodes)| nodes).sum::<u64>(), 400);
+}
+
+// Deeper counts take minutes in debug builds; run with `cargo test --release -- --ignored`.
+#[test]
+#[ignore]
+fn test_perft_deep() {
+    assert_perft(INITIAL_POSITION_FEN, &[20, 400, 8902, 197281, 4865609]);
+    assert_perft(KIWIPETE_FEN, &[48, 2039, 97862, 4085603]);
+    assert_perft(POSITION_3_FEN, &[14, 191, 2812, 43238, 674624]);
+    assert_perft(POSITION_4_FEN, &[6, 264, 9467, 422333]);
+    assert_perft(POSITION_5_FEN, &[44, 1486, 62379, 2103487]);
+    assert_perft(POSITION_6_FEN, &[46, 2079, 89890, 3894594]);
+}
@@ -3,22 +3,34 @@ use rocket::log::private::debug;
 use super::bitboard::SpecialMoveType;
 use super::zobrist::ZobristTable;
 use crate::chess::bitboard::{
-    BitArraySize, BitB64, BitboardMove, PlayerBitboard, EMPTY_BOARD, FULL_BOARD,
+    self, BitArraySize, BitB64, BitboardMove, PlayerBitboard, DARK_SQUARES, EMPTY_BOARD, FULL_BOARD,
 };
+use crate::evaluation::material_evaluator::PIECE_VALUES;
+use crate::move_gen::attacks::attackers_to;
 use crate::move_gen::internal::intersect;
 use strum::IntoEnumIterator;
 
 use crate::chess::{ChessPiece, PieceType, PlayerColor};
-use crate::move_gen::{self, MoveGenOpts, MoveGenPerspective};
+use crate::move_gen::{self, MoveGenMode, MoveGenOpts, MoveGenPerspective};
 use crate::move_gen::{
-    bishop::BishopBitboardMoveGenerator, king::KingBitboardMoveGenerator,
-    knight::KnightBitboardMoveGenerator, merge_moves_map, pawn::PawnBitboardMoveGenerator,
-    queen::QueenBitboardMoveGenerator, rook::RookBitboardMoveGenerator, BitboardMoveGenerator,
-    MovesMap, PieceAndMoves,
+    bishop::BishopBitboardMoveGenerator,
+    king::KingBitboardMoveGenerator,
+    knight::KnightBitboardMoveGenerator,
+    legal::CheckInfo,
+    move_list::{Move, MoveList},
+    pawn::PawnBitboardMoveGenerator,
+    queen::QueenBitboardMoveGenerator,
+    rook::RookBitboardMoveGenerator,
+    BitboardMoveGenerator,
 };
 use crate::UciRequest;
 use rand::{thread_rng, Rng};
 
+pub const INITIAL_POSITION_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+// The first and last ranks.
+const BACK_RANKS: u64 = 0xff00_0000_0000_00ff;
+
 pub struct PositionScore {
     pub score: i32,
     // 0 by default. Different than 0 means that the position is a mate in x moves.
@@ -29,7 +41,7 @@ pub struct PositionScore {
     // bit 3: white_king_in_check.
     // bit 4: black_king_in_check.
     pub metadata: u8,
-    // Bitboard of pinned pieces.
+    // Pieces of the player to move pinned against their king.
     pub pinned_pieces: u64,
 }
 pub struct ScoredPosition {
@@ -40,7 +52,7 @@ pub struct ScoredPosition {
 enum PositionInfoMetadataBits {
     PlayerToMove,
 }
-#[derive(EnumIter)]
+#[derive(EnumIter, Clone, Copy)]
 pub enum CastlingRightsBits {
     WhiteShortCastlingRights,
     WhiteLongCastlingRights,
@@ -48,7 +60,7 @@ pub enum CastlingRightsBits {
     BlackLongCastlingRights,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct PositionInfo {
     pub white_unused_en_passant: u8,
     pub black_unused_en_passant: u8,
@@ -67,6 +79,10 @@ pub struct PositionInfo {
     // ...
     pub metadata: u8,
     pub zobrist_hash: u64,
+    // Halfmoves since the last capture or pawn advance.
+    pub halfmove_clock: u16,
+    // Starts at 1 and is incremented after every black move.
+    pub fullmove_number: u16,
 }
 
 impl PositionInfo {
@@ -79,6 +95,8 @@ impl PositionInfo {
             castling_rights: 0,
             metadata: 0,
             zobrist_hash: 0,
+            halfmove_clock: 0,
+            fullmove_number: 1,
         };
         result
     }
@@ -126,36 +144,112 @@ impl PositionInfo {
         !self.white_to_move()
     }
 
+    pub fn has_castling_rights_bit(&self, bit: CastlingRightsBits) -> bool {
+        (self.castling_rights & u8::nth(bit as u8)) != 0
+    }
+
+    pub fn set_castling_rights_bit(&mut self, bit: CastlingRightsBits) {
+        self.castling_rights = u8::enable_nth(self.castling_rights, bit as u8);
+    }
+
+    // Square the player to move can capture into en passant, if any.
+    pub fn en_passant_target_sq_id(&self) -> Option<u8> {
+        let (usable_files, target_rank_offset) = match self.player_to_move() {
+            PlayerColor::White => (self.white_usable_en_passant, bitboard::A6),
+            PlayerColor::Black => (self.black_usable_en_passant, bitboard::A3),
+        };
+        if usable_files == 0 {
+            None
+        } else {
+            Some(target_rank_offset + usable_files.trailing_zeros() as u8)
+        }
+    }
+
+    // Records |sq_id| (on the third or sixth rank) as capturable en passant by the player
+    // whose pawns attack it.
+    pub fn set_en_passant_target_sq_id(&mut self, sq_id: u8) {
+        let file = sq_id % 8;
+        if sq_id / 8 == 5 {
+            self.white_usable_en_passant = u8::nth(file);
+        } else {
+            self.black_usable_en_passant = u8::nth(file);
+        }
+    }
+
     pub fn has_short_castling_rights(&self, color: PlayerColor) -> bool {
-        true
-        // match color {
-        //     PlayerColor::White => {
-        //         (self.castling_rights & u8::nth(CastlingRightsBits::WhiteShortCastlingRights as u8))
-        //             != 0
-        //     }
-        //     PlayerColor::Black => {
-        //         (self.castling_rights & u8::nth(CastlingRightsBits::BlackShortCastlingRights as u8))
-        //             != 0
-        //     }
-        // }
+        match color {
+            PlayerColor::White => {
+                self.has_castling_rights_bit(CastlingRightsBits::WhiteShortCastlingRights)
+            }
+            PlayerColor::Black => {
+                self.has_castling_rights_bit(CastlingRightsBits::BlackShortCastlingRights)
+            }
+        }
     }
     pub fn has_long_castling_rights(&self, color: PlayerColor) -> bool {
-        true
-        // match color {
-        //     PlayerColor::White => {
-        //         (self.castling_rights & u8::nth(CastlingRightsBits::WhiteLongCastlingRights as u8))
-        //             != 0
-        //     }
-        //     PlayerColor::Black => {
-        //         (self.castling_rights & u8::nth(CastlingRightsBits::BlackLongCastlingRights as u8))
-        //             != 0
-        //     }
-        // }
+        match color {
+            PlayerColor::White => {
+                self.has_castling_rights_bit(CastlingRightsBits::WhiteLongCastlingRights)
+            }
+            PlayerColor::Black => {
+                self.has_castling_rights_bit(CastlingRightsBits::BlackLongCastlingRights)
+            }
+        }
+    }
+}
+
+// Castling rights lost when a piece moves from or to |sq_id|.
+fn castling_rights_lost_on_sq(sq_id: u8) -> u8 {
+    match sq_id {
+        bitboard::A1 => u8::nth(CastlingRightsBits::WhiteLongCastlingRights as u8),
+        bitboard::H1 => u8::nth(CastlingRightsBits::WhiteShortCastlingRights as u8),
+        bitboard::E1 => {
+            u8::nth(CastlingRightsBits::WhiteShortCastlingRights as u8)
+                | u8::nth(CastlingRightsBits::WhiteLongCastlingRights as u8)
+        }
+        bitboard::A8 => u8::nth(CastlingRightsBits::BlackLongCastlingRights as u8),
+        bitboard::H8 => u8::nth(CastlingRightsBits::BlackShortCastlingRights as u8),
+        bitboard::E8 => {
+            u8::nth(CastlingRightsBits::BlackShortCastlingRights as u8)
+                | u8::nth(CastlingRightsBits::BlackLongCastlingRights as u8)
+        }
+        _ => 0,
+    }
+}
+
+// What make_move_in_place() overwrites, for unmake_move() to restore.
+struct Undo {
+    mv: Move,
+    captured: Option<PieceType>,
+    // Castling rights, en passant target, halfmove clock and zobrist hash before the move.
+    position_info: PositionInfo,
+    // Only kept to check that unmaking the move restores the exact position.
+    #[cfg(debug_assertions)]
+    before: Position,
+}
+
+// Moves made in place, latest last.
+#[derive(Default)]
+pub struct UndoStack {
+    entries: Vec<Undo>,
+}
+
+impl UndoStack {
+    pub fn new() -> Self {
+        UndoStack { entries: vec![] }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
     }
 }
 
 // Bitboard representation of a chess position.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct Position {
     pub white: PlayerBitboard,
     pub black: PlayerBitboard,
@@ -164,11 +258,16 @@ pub struct Position {
 
 impl Position {
     pub fn new() -> Position {
-        Position {
+        let mut result = Position {
             white: PlayerBitboard::new(PlayerColor::White),
             black: PlayerBitboard::new(PlayerColor::Black),
             position_info: PositionInfo::new(),
+        };
+        for bit in CastlingRightsBits::iter() {
+            result.position_info.set_castling_rights_bit(bit);
         }
+        result.compute_zobrist_hash();
+        result
     }
 
     pub fn decode_pieces(board: &String) -> (PlayerBitboard, PlayerBitboard) {
@@ -217,11 +316,222 @@ impl Position {
         // fill bitboards
         let (white, black) = Self::decode_pieces(&uci_req.board);
 
-        Position {
+        let mut result = Position {
             white: white,
             black: black,
             position_info: pos_info,
+        };
+        result.infer_castling_rights();
+        result.compute_zobrist_hash();
+        result
+    }
+
+    // The UCI request carries no castling information, so grant every right whose king and
+    // rook are still on their initial squares.
+    fn infer_castling_rights(&mut self) {
+        for bit in CastlingRightsBits::iter() {
+            if self.castling_pieces_at_home(bit) {
+                self.position_info.set_castling_rights_bit(bit);
+            }
+        }
+    }
+
+    // Whether the king and the rook that castle with |bit| are on their initial squares.
+    fn castling_pieces_at_home(&self, bit: CastlingRightsBits) -> bool {
+        let (pieces, king_sq_id, rook_sq_id) = match bit {
+            CastlingRightsBits::WhiteShortCastlingRights => {
+                (&self.white, bitboard::E1, bitboard::H1)
+            }
+            CastlingRightsBits::WhiteLongCastlingRights => {
+                (&self.white, bitboard::E1, bitboard::A1)
+            }
+            CastlingRightsBits::BlackShortCastlingRights => {
+                (&self.black, bitboard::E8, bitboard::H8)
+            }
+            CastlingRightsBits::BlackLongCastlingRights => {
+                (&self.black, bitboard::E8, bitboard::A8)
+            }
+        };
+        intersect(pieces.king, u64::nth(king_sq_id))
+            && intersect(pieces.rooks, u64::nth(rook_sq_id))
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Position, String> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 || fields.len() > 6 {
+            return Err(format!("expected 4 to 6 FEN fields, got {}", fields.len()));
+        }
+        let mut result = Position {
+            white: PlayerBitboard::empty(),
+            black: PlayerBitboard::empty(),
+            position_info: PositionInfo::new(),
+        };
+
+        // Piece placement, from the eighth rank down to the first.
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err(format!("expected 8 ranks, got {}", ranks.len()));
+        }
+        for (rank_idx, rank) in ranks.iter().enumerate() {
+            let row = 7 - rank_idx as u8;
+            let mut col = 0u8;
+            for ch in rank.chars() {
+                // Runs of empty squares, which must not go past the end of the rank.
+                if let Some(skip) = ch.to_digit(10) {
+                    if skip == 0 || col as u32 + skip > 8 {
+                        return Err(format!("rank {} does not have 8 squares", row + 1));
+                    }
+                    col += skip as u8;
+                    continue;
+                }
+                let piece = ChessPiece::from_fen_char(ch)
+                    .ok_or_else(|| format!("invalid piece '{}' in FEN", ch))?;
+                if col >= 8 {
+                    return Err(format!("rank {} has more than 8 squares", row + 1));
+                }
+                let pieces = match piece.color {
+                    PlayerColor::White => &mut result.white,
+                    PlayerColor::Black => &mut result.black,
+                };
+                *pieces.mut_pieces(piece.typpe) |= u64::nth(row * 8 + col);
+                col += 1;
+            }
+            if col != 8 {
+                return Err(format!("rank {} does not have 8 squares", row + 1));
+            }
+        }
+        if result.white.king.count_ones() != 1 || result.black.king.count_ones() != 1 {
+            return Err("each side must have exactly one king".to_string());
+        }
+        // Pawns promote on reaching the last rank, and never go back to their first one.
+        if intersect(result.white.pawns | result.black.pawns, BACK_RANKS) {
+            return Err("pawns cannot stand on the first or last rank".to_string());
+        }
+
+        result.position_info.set_player_to_move(match fields[1] {
+            "w" => PlayerColor::White,
+            "b" => PlayerColor::Black,
+            other => return Err(format!("invalid side to move '{}'", other)),
+        });
+
+        // Rights whose king or rook has left its initial square are dropped, as the moves
+        // they allow would be illegal.
+        if fields[2] != "-" {
+            for ch in fields[2].chars() {
+                let bit = match ch {
+                    'K' => CastlingRightsBits::WhiteShortCastlingRights,
+                    'Q' => CastlingRightsBits::WhiteLongCastlingRights,
+                    'k' => CastlingRightsBits::BlackShortCastlingRights,
+                    'q' => CastlingRightsBits::BlackLongCastlingRights,
+                    other => return Err(format!("invalid castling right '{}'", other)),
+                };
+                if result.castling_pieces_at_home(bit) {
+                    result.position_info.set_castling_rights_bit(bit);
+                }
+            }
+        }
+
+        if fields[3] != "-" {
+            let sq_id = bitboard::sq_id_from_coordinates(fields[3])
+                .ok_or_else(|| format!("invalid en passant square '{}'", fields[3]))?;
+            let expected_row = match result.player_to_move() {
+                PlayerColor::White => 5,
+                PlayerColor::Black => 2,
+            };
+            if sq_id / 8 != expected_row {
+                return Err(format!("invalid en passant square '{}'", fields[3]));
+            }
+            result.position_info.set_en_passant_target_sq_id(sq_id);
+        }
+
+        if let Some(halfmove_clock) = fields.get(4) {
+            result.position_info.halfmove_clock = halfmove_clock
+                .parse()
+                .map_err(|_| format!("invalid halfmove clock '{}'", halfmove_clock))?;
+        }
+        if let Some(fullmove_number) = fields.get(5) {
+            result.position_info.fullmove_number = fullmove_number
+                .parse()
+                .map_err(|_| format!("invalid fullmove number '{}'", fullmove_number))?;
+        }
+
+        result.compute_zobrist_hash();
+        Ok(result)
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut placement = String::new();
+        for row in (0..8u8).rev() {
+            let mut empty_squares = 0;
+            for col in 0..8u8 {
+                match self.piece_at(row * 8 + col) {
+                    Some(piece) => {
+                        if empty_squares > 0 {
+                            placement += &empty_squares.to_string();
+                            empty_squares = 0;
+                        }
+                        placement.push(piece.to_fen_char());
+                    }
+                    None => empty_squares += 1,
+                }
+            }
+            if empty_squares > 0 {
+                placement += &empty_squares.to_string();
+            }
+            if row > 0 {
+                placement.push('/');
+            }
+        }
+
+        let side_to_move = match self.player_to_move() {
+            PlayerColor::White => "w",
+            PlayerColor::Black => "b",
+        };
+
+        let mut castling = String::new();
+        for (bit, ch) in [
+            (CastlingRightsBits::WhiteShortCastlingRights, 'K'),
+            (CastlingRightsBits::WhiteLongCastlingRights, 'Q'),
+            (CastlingRightsBits::BlackShortCastlingRights, 'k'),
+            (CastlingRightsBits::BlackLongCastlingRights, 'q'),
+        ] {
+            if self.position_info.has_castling_rights_bit(bit) {
+                castling.push(ch);
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let en_passant = match self.position_info.en_passant_target_sq_id() {
+            Some(sq_id) => bitboard::sq_id_to_coordinates(sq_id),
+            None => "-".to_string(),
+        };
+
+        format!(
+            "{} {} {} {} {} {}",
+            placement,
+            side_to_move,
+            castling,
+            en_passant,
+            self.position_info.halfmove_clock,
+            self.position_info.fullmove_number
+        )
+    }
+
+    pub fn piece_at(&self, sq_id: u8) -> Option<ChessPiece> {
+        let sq = u64::nth(sq_id);
+        for (color, pieces) in [
+            (PlayerColor::White, &self.white),
+            (PlayerColor::Black, &self.black),
+        ] {
+            for typpe in PieceType::iter() {
+                if intersect(*pieces.pieces(typpe), sq) {
+                    return Some(ChessPiece { typpe, color });
+                }
+            }
         }
+        None
     }
 
     pub fn pass_turn(&mut self) -> () {
@@ -243,6 +553,7 @@ impl Position {
                 &self,
                 MoveGenOpts {
                     perspective: *perspective,
+                    mode: MoveGenMode::AllMoves,
                 },
             )
         }
@@ -300,7 +611,9 @@ impl Position {
                 table.castling_rights[CastlingRightsBits::BlackLongCastlingRights as usize];
         }
 
-        // TODO implement enpassant.
+        if let Some(sq_id) = self.position_info.en_passant_target_sq_id() {
+            self.position_info.zobrist_hash ^= table.en_passant[sq_id as usize];
+        }
 
         // XOR with random value for side to move
         if self.player_to_move() == PlayerColor::Black {
@@ -308,6 +621,25 @@ impl Position {
         }
     }
 
+    // Zobrist hash of the pawns alone, the key of the pawn hash table. Computed on demand
+    // rather than kept up to date by make_move(): there are at most 16 pawns to hash.
+    pub fn pawn_hash(&self) -> u64 {
+        let table = ZobristTable::get();
+        let mut result = 0;
+        for (color, pieces) in [
+            (PlayerColor::White, &self.white),
+            (PlayerColor::Black, &self.black),
+        ] {
+            let mut piece_set = pieces.pawns;
+            while piece_set != EMPTY_BOARD {
+                let sq_id = piece_set.trailing_zeros() as u8;
+                result ^= table.table[PieceType::Pawn as usize][color as usize][sq_id as usize];
+                piece_set ^= u64::nth(sq_id);
+            }
+        }
+        result
+    }
+
     pub fn player_to_move(&self) -> PlayerColor {
         self.position_info.player_to_move()
     }
@@ -344,7 +676,38 @@ impl Position {
         }
     }
 
-    pub fn update_info(&mut self) {
+    // 100 plies without a capture or a pawn move.
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.position_info.halfmove_clock >= 100
+    }
+
+    // Neither player can checkmate, whatever the moves: K v K, KB v K, KN v K, and kings with
+    // bishops that all stand on squares of the same color.
+    pub fn has_insufficient_material(&self) -> bool {
+        let (white, black) = (&self.white, &self.black);
+        if white.pawns | black.pawns | white.rooks | black.rooks | white.queens | black.queens
+            != EMPTY_BOARD
+        {
+            return false;
+        }
+        let knights = white.knights | black.knights;
+        let bishops = white.bishops | black.bishops;
+        if knights == EMPTY_BOARD {
+            bishops & DARK_SQUARES == EMPTY_BOARD || bishops & !DARK_SQUARES == EMPTY_BOARD
+        } else {
+            bishops == EMPTY_BOARD && knights.count_ones() == 1
+        }
+    }
+
+    pub fn update_info(&mut self, resets_halfmove_clock: bool) {
+        if resets_halfmove_clock {
+            self.position_info.halfmove_clock = 0;
+        } else {
+            self.position_info.halfmove_clock = self.position_info.halfmove_clock.saturating_add(1);
+        }
+        if self.player_to_move() == PlayerColor::Black {
+            self.position_info.fullmove_number += 1;
+        }
         self.pass_turn();
     }
     // Returns the zobrist mutation to be applied to the position
@@ -383,72 +746,268 @@ impl Position {
         let piece_set = self.mut_pieces_to_move().mut_pieces(typpe);
         *piece_set ^= to_sq;
         self.mut_pieces_to_move().pawns ^= to_sq;
+        let to = to_sq.trailing_zeros() as usize;
+        zobrist_mutation ^= z_table.table[typpe as usize][self.player_to_move() as usize][to];
         zobrist_mutation ^=
-            z_table.table[typpe as usize][self.player_to_move() as usize][to_sq as usize];
-        zobrist_mutation ^=
-            z_table.table[PieceType::Pawn as usize][self.waiting_player() as usize][to_sq as usize];
+            z_table.table[PieceType::Pawn as usize][self.player_to_move() as usize][to];
+        zobrist_mutation
+    }
+
+    // Drops the castling rights invalidated by |mv|: the king leaving its square, or a rook
+    // leaving or being captured on its initial square. Returns the zobrist mutation to be
+    // applied to the position.
+    fn update_castling_rights(&mut self, mv: &BitboardMove) -> u64 {
+        let z_table = ZobristTable::get();
+        let mut zobrist_mutation = 0;
+        let lost_rights = self.position_info.castling_rights
+            & (castling_rights_lost_on_sq(mv.from) | castling_rights_lost_on_sq(mv.to));
+        for bit in CastlingRightsBits::iter() {
+            if intersect(lost_rights as u64, u64::nth(bit as u8)) {
+                zobrist_mutation ^= z_table.castling_rights[bit as usize];
+            }
+        }
+        self.position_info.castling_rights ^= lost_rights;
+        zobrist_mutation
+    }
+
+    // Forgets the previous en passant target and records a new one after a double pawn push.
+    // Returns the zobrist mutation to be applied to the position.
+    fn update_en_passant(&mut self, mv: &BitboardMove, piece: ChessPiece) -> u64 {
+        let z_table = ZobristTable::get();
+        let mut zobrist_mutation = 0;
+        if let Some(sq_id) = self.position_info.en_passant_target_sq_id() {
+            zobrist_mutation ^= z_table.en_passant[sq_id as usize];
+        }
+        self.position_info.white_usable_en_passant = 0;
+        self.position_info.black_usable_en_passant = 0;
+        if piece.typpe == PieceType::Pawn && mv.from.abs_diff(mv.to) == 16 {
+            let target_sq_id = (mv.from + mv.to) / 2;
+            self.position_info.set_en_passant_target_sq_id(target_sq_id);
+            zobrist_mutation ^= z_table.en_passant[target_sq_id as usize];
+        }
         zobrist_mutation
     }
 
     pub fn make_move(&self, mv: &BitboardMove, piece: ChessPiece) -> Position {
-        // Remove the piece from its old position.
-        // gets black or white bitboard
         let mut result = *self;
-        let (ally_pieces, mut enemy_pieces) = match piece.color {
-            PlayerColor::White => (&mut result.white, &mut result.black),
-            PlayerColor::Black => (&mut result.black, &mut result.white),
+        result.apply_move(mv, piece);
+        result
+    }
+
+    fn apply_move(&mut self, mv: &BitboardMove, piece: ChessPiece) {
+        let is_capture = intersect(self.enemy_pieces().all_pieces(), u64::nth(mv.to));
+        let players = (self.player_to_move(), self.waiting_player());
+        let (ally_pieces, enemy_pieces) = match piece.color {
+            PlayerColor::White => (&mut self.white, &mut self.black),
+            PlayerColor::Black => (&mut self.black, &mut self.white),
         };
-        result.position_info.zobrist_hash ^= Self::make_raw_bitboard_move(
+        let mut zobrist_mutation = Self::make_raw_bitboard_move(
             (mv.from, mv.to),
-            (self.player_to_move(), self.waiting_player()),
+            players,
             (ally_pieces, enemy_pieces),
             piece.typpe,
         );
         match mv.sp_move_type {
-            SpecialMoveType::RegularMove => (),
             SpecialMoveType::ShortCastle => {
-                result.position_info.zobrist_hash ^= Self::make_raw_bitboard_move(
+                zobrist_mutation ^= Self::make_raw_bitboard_move(
                     move_gen::rook::get_rook_move_for_short_castle(piece.color),
-                    (self.player_to_move(), self.waiting_player()),
+                    players,
                     (ally_pieces, enemy_pieces),
-                    piece.typpe,
+                    PieceType::Rook,
                 );
             }
             SpecialMoveType::LongCastle => {
-                result.position_info.zobrist_hash ^= Self::make_raw_bitboard_move(
+                zobrist_mutation ^= Self::make_raw_bitboard_move(
                     move_gen::rook::get_rook_move_for_long_castle(piece.color),
-                    (self.player_to_move(), self.waiting_player()),
+                    players,
                     (ally_pieces, enemy_pieces),
-                    piece.typpe,
+                    PieceType::Rook,
                 );
             }
-            SpecialMoveType::EnPassantLeft => todo!(),
-            SpecialMoveType::EnPassantRight => todo!(),
-            SpecialMoveType::PromotionToBishop => {
-                let to_sq = u64::nth(mv.to);
-                result.position_info.zobrist_hash ^=
-                    result.execute_promotion(PieceType::Bishop, to_sq);
+            SpecialMoveType::EnPassantLeft | SpecialMoveType::EnPassantRight => {
+                // The captured pawn sits next to the moving one, on the destination file.
+                let captured_sq_id = (mv.from / 8) * 8 + mv.to % 8;
+                enemy_pieces.pawns ^= u64::nth(captured_sq_id);
+                zobrist_mutation ^= ZobristTable::get().table[PieceType::Pawn as usize]
+                    [players.1 as usize][captured_sq_id as usize];
             }
-            SpecialMoveType::PromotionToKnight => {
-                let to_sq = u64::nth(mv.to);
-                result.position_info.zobrist_hash ^=
-                    result.execute_promotion(PieceType::Knight, to_sq);
+            _ => (),
+        }
+        if let Some(typpe) = mv.sp_move_type.promotion_piece_type() {
+            zobrist_mutation ^= self.execute_promotion(typpe, u64::nth(mv.to));
+        }
+        zobrist_mutation ^= self.update_castling_rights(mv);
+        zobrist_mutation ^= self.update_en_passant(mv, piece);
+        self.position_info.zobrist_hash ^= zobrist_mutation;
+        self.update_info(is_capture || piece.typpe == PieceType::Pawn);
+    }
+
+    // Type of the piece |mv| captures, if any.
+    pub fn captured_piece_type(&self, mv: &Move) -> Option<PieceType> {
+        match mv.sp_move_type() {
+            SpecialMoveType::EnPassantLeft | SpecialMoveType::EnPassantRight => {
+                Some(PieceType::Pawn)
             }
-            SpecialMoveType::PromotionToRook => {
-                let to_sq = u64::nth(mv.to);
-                result.position_info.zobrist_hash ^=
-                    result.execute_promotion(PieceType::Rook, to_sq);
+            _ => self.piece_at(mv.to()).map(|piece| piece.typpe),
+        }
+    }
+
+    // Static exchange evaluation: material won by the player to move with |mv|, once both
+    // players have kept recapturing on its destination with their least valuable piece, each
+    // free to stop when recapturing would lose material. Sliders lined up behind a capturing
+    // piece join in once it is gone (x-rays). Pins, and promotions after the first move, are
+    // ignored.
+    pub fn see(&self, mv: &Move) -> i32 {
+        let value = |typpe: PieceType| PIECE_VALUES[typpe as usize];
+        let to = mv.to();
+        let mut occupancy =
+            (self.white.all_pieces() | self.black.all_pieces()) ^ u64::nth(mv.from());
+        match mv.sp_move_type() {
+            SpecialMoveType::ShortCastle | SpecialMoveType::LongCastle => return 0,
+            SpecialMoveType::EnPassantLeft | SpecialMoveType::EnPassantRight => {
+                occupancy ^= u64::nth(mv.from() - mv.from() % 8 + to % 8);
             }
-            SpecialMoveType::PromotionToQueen => {
-                let to_sq = u64::nth(mv.to);
-                result.position_info.zobrist_hash ^=
-                    result.execute_promotion(PieceType::Queen, to_sq);
+            _ => (),
+        }
+
+        // |gains[i]| is the material won by the player making the i-th capture, assuming the
+        // exchange stops right after it. Every piece can capture at most once.
+        let mut gains = [0; 33];
+        gains[0] = self.captured_piece_type(mv).map_or(0, value);
+        // Value of the piece standing on the destination, for the next capture to win.
+        let mut target_value = value(mv.piece_type());
+        if let Some(promoted) = mv.sp_move_type().promotion_piece_type() {
+            gains[0] += value(promoted) - value(PieceType::Pawn);
+            target_value = value(promoted);
+        }
+        let mut color = self.waiting_player();
+        let mut depth = 0;
+        loop {
+            depth += 1;
+            // Material |color| wins by capturing next, if it can.
+            gains[depth] = target_value - gains[depth - 1];
+            let (pieces, enemies) = match color {
+                PlayerColor::White => (&self.white, &self.black),
+                PlayerColor::Black => (&self.black, &self.white),
+            };
+            let attackers = attackers_to(to, pieces, color, occupancy) & occupancy;
+            let Some(typpe) =
+                PieceType::iter().find(|typpe| *pieces.pieces(*typpe) & attackers != EMPTY_BOARD)
+            else {
+                break;
+            };
+            occupancy ^= u64::nth((*pieces.pieces(typpe) & attackers).trailing_zeros() as u8);
+            // The king cannot capture a defended piece.
+            if typpe == PieceType::King
+                && attackers_to(to, enemies, PlayerColor::other(color), occupancy) & occupancy
+                    != EMPTY_BOARD
+            {
+                break;
             }
+            target_value = value(typpe);
+            color = PlayerColor::other(color);
         }
-        // TODO(implement castling info updates.)
-        result.update_info();
-        // Return mutable reference to self to allow chaining calls.
-        result
+        // The last capture was not made. Going backwards, each player either makes their
+        // capture or stops before it, whichever wins them the most.
+        while depth > 1 {
+            depth -= 1;
+            gains[depth - 1] = -(-gains[depth - 1]).max(gains[depth]);
+        }
+        gains[0]
+    }
+
+    // Makes |mv|, a move of the player to move, without copying the position. Whatever the
+    // move overwrites is pushed to |undo_stack| for unmake_move() to restore.
+    pub fn make_move_in_place(&mut self, mv: Move, undo_stack: &mut UndoStack) {
+        let captured = self.captured_piece_type(&mv);
+        undo_stack.entries.push(Undo {
+            mv,
+            captured,
+            position_info: self.position_info,
+            #[cfg(debug_assertions)]
+            before: *self,
+        });
+        self.apply_move(&mv.bitboard_move(), mv.piece(self.player_to_move()));
+    }
+
+    // Takes back the last move made with make_move_in_place() on |undo_stack|.
+    pub fn unmake_move(&mut self, undo_stack: &mut UndoStack) {
+        let undo = undo_stack
+            .entries
+            .pop()
+            .expect("unmake_move() without a move to take back");
+        let mv = undo.mv;
+        // Restores the player to move, castling rights, en passant target, clocks and hash.
+        self.position_info = undo.position_info;
+        let (from_sq, to_sq) = (u64::nth(mv.from()), u64::nth(mv.to()));
+        let color = self.player_to_move();
+        let (ally_pieces, enemy_pieces) = match color {
+            PlayerColor::White => (&mut self.white, &mut self.black),
+            PlayerColor::Black => (&mut self.black, &mut self.white),
+        };
+        let landed_type = mv
+            .sp_move_type()
+            .promotion_piece_type()
+            .unwrap_or(mv.piece_type());
+        *ally_pieces.mut_pieces(landed_type) ^= to_sq;
+        *ally_pieces.mut_pieces(mv.piece_type()) ^= from_sq;
+        match mv.sp_move_type() {
+            SpecialMoveType::ShortCastle | SpecialMoveType::LongCastle => {
+                let (rook_from, rook_to) = match mv.sp_move_type() {
+                    SpecialMoveType::ShortCastle => {
+                        move_gen::rook::get_rook_move_for_short_castle(color)
+                    }
+                    _ => move_gen::rook::get_rook_move_for_long_castle(color),
+                };
+                ally_pieces.rooks ^= u64::nth(rook_from) | u64::nth(rook_to);
+            }
+            SpecialMoveType::EnPassantLeft | SpecialMoveType::EnPassantRight => {
+                let captured_sq_id = (mv.from() / 8) * 8 + mv.to() % 8;
+                enemy_pieces.pawns ^= u64::nth(captured_sq_id);
+            }
+            _ => {
+                if let Some(captured) = undo.captured {
+                    *enemy_pieces.mut_pieces(captured) ^= to_sq;
+                }
+            }
+        }
+        #[cfg(debug_assertions)]
+        debug_assert_eq!(
+            *self, undo.before,
+            "unmaking {:?} did not restore the position",
+            mv
+        );
+    }
+
+    // Whether the king of the player to move is attacked. Cheaper than can_king_be_captured(),
+    // which looks at every square the enemy attacks.
+    pub fn in_check(&self) -> bool {
+        let king = self.pieces_to_move().king;
+        let occupancy = self.white.all_pieces() | self.black.all_pieces();
+        king != EMPTY_BOARD
+            && attackers_to(
+                king.trailing_zeros() as u8,
+                self.enemy_pieces(),
+                self.waiting_player(),
+                occupancy,
+            ) != EMPTY_BOARD
+    }
+
+    // Whether the player to move has pieces other than pawns. Without them, zugzwang is common.
+    pub fn has_non_pawn_material(&self) -> bool {
+        let pieces = self.pieces_to_move();
+        pieces.knights | pieces.bishops | pieces.rooks | pieces.queens != EMPTY_BOARD
+    }
+
+    // Passes the turn without moving, for null-move pruning. As after any move, the en passant
+    // target expires.
+    pub fn make_null_move(&mut self) {
+        if let Some(sq_id) = self.position_info.en_passant_target_sq_id() {
+            self.position_info.zobrist_hash ^= ZobristTable::get().en_passant[sq_id as usize];
+        }
+        self.position_info.white_usable_en_passant = 0;
+        self.position_info.black_usable_en_passant = 0;
+        self.update_info(false);
     }
 
     // Returns whether king of given |color| can be capturued.
@@ -472,62 +1031,56 @@ impl Position {
         new.can_king_be_captured(MoveGenPerspective::WaitingPlayer)
     }
 
-    pub fn legal_continuations(&self) -> MovesMap {
-        let possible_moves_map = self.pseudolegal_continuations();
-        let mut result = MovesMap::new();
-        // For each square, we know if there's a piece in it pseudolegal moves.
-        for (from_id, piece_and_moves) in possible_moves_map.iter() {
-            let typpe = piece_and_moves.typpe;
-            let moves_list = &piece_and_moves.moves;
-            let mut legal_moves = Vec::new();
-            for mv in moves_list.iter() {
-                if !self.move_puts_own_king_in_check(
-                    &mv,
-                    ChessPiece {
-                        typpe: typpe,
-                        color: self.player_to_move(),
-                    },
-                ) {
-                    legal_moves.push(*mv);
-                }
-            }
-            if legal_moves.len() > 0 {
-                result.insert(
-                    *from_id,
-                    PieceAndMoves {
-                        typpe: typpe,
-                        moves: legal_moves,
-                    },
-                );
-            }
-        }
+    pub fn legal_continuations(&self) -> MoveList {
+        self.legal_moves(MoveGenMode::AllMoves)
+    }
+
+    // Legal captures and promotions, for the quiescence search.
+    pub fn legal_captures(&self) -> MoveList {
+        self.legal_moves(MoveGenMode::CapturesAndPromotions)
+    }
+
+    pub fn legal_moves(&self, mode: MoveGenMode) -> MoveList {
+        let mut result = self.pseudolegal_moves(mode);
+        let check_info = CheckInfo::new(self);
+        result.retain(|mv| check_info.is_legal(self, mv));
         result
     }
 
-    pub fn pseudolegal_continuations(&self) -> MovesMap {
-        let mut result = MovesMap::new();
+    // Pieces of the player to move pinned against their king.
+    pub fn pinned_pieces(&self) -> BitB64 {
+        CheckInfo::new(self).pinned
+    }
 
-        let piece_generators = vec![
-            PawnBitboardMoveGenerator::generate_moves,
-            KnightBitboardMoveGenerator::generate_moves,
-            BishopBitboardMoveGenerator::generate_moves,
-            RookBitboardMoveGenerator::generate_moves,
-            QueenBitboardMoveGenerator::generate_moves,
-            KingBitboardMoveGenerator::generate_moves,
-        ];
+    // Finds the legal move written in coordinate notation ("e2e4", "e7e8q").
+    pub fn find_legal_move(&self, coordinates: &str) -> Option<Move> {
+        self.legal_continuations()
+            .iter()
+            .find(|mv| mv.to_coordinates() == coordinates)
+            .copied()
+    }
 
-        for generate_moves in piece_generators.iter() {
-            merge_moves_map(
-                generate_moves(
-                    &self,
-                    MoveGenOpts {
-                        perspective: MoveGenPerspective::MovingPlayer,
-                    },
-                ),
-                &mut result,
-            );
-        }
+    // Same as make_move(), for a move taken from a move list of this position.
+    pub fn play_move(&self, mv: Move) -> Position {
+        self.make_move(&mv.bitboard_move(), mv.piece(self.player_to_move()))
+    }
 
+    pub fn pseudolegal_continuations(&self) -> MoveList {
+        self.pseudolegal_moves(MoveGenMode::AllMoves)
+    }
+
+    pub fn pseudolegal_moves(&self, mode: MoveGenMode) -> MoveList {
+        let mut result = MoveList::new();
+        let opts = MoveGenOpts {
+            perspective: MoveGenPerspective::MovingPlayer,
+            mode,
+        };
+        PawnBitboardMoveGenerator::generate_moves(self, opts, &mut result);
+        KnightBitboardMoveGenerator::generate_moves(self, opts, &mut result);
+        BishopBitboardMoveGenerator::generate_moves(self, opts, &mut result);
+        RookBitboardMoveGenerator::generate_moves(self, opts, &mut result);
+        QueenBitboardMoveGenerator::generate_moves(self, opts, &mut result);
+        KingBitboardMoveGenerator::generate_moves(self, opts, &mut result);
         result
     }
 }
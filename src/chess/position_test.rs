@@ -0,0 +1,457 @@
+use super::{
+    bitboard::{
+        BitArraySize, BitboardMove, SpecialMoveType, A1, A8, B5, C1, D3, D6, E1, E2, E4, E5, E7,
+        E8, F3, F6, G1, G8, H1, H2,
+    },
+    position::{Position, UndoStack, INITIAL_POSITION_FEN},
+    ChessPiece, PieceType, PlayerColor,
+};
+use crate::move_gen::{
+    king::KingBitboardMoveGenerator, move_list::MoveList, BitboardMoveGenerator, MoveGenMode,
+    MoveGenOpts, MoveGenPerspective,
+};
+
+const KIWIPETE_FEN: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+#[test]
+fn test_initial_fen_matches_initial_position() {
+    let from_fen = Position::from_fen(INITIAL_POSITION_FEN).unwrap();
+    let initial = Position::new();
+    assert_eq!(from_fen.white.all_pieces(), initial.white.all_pieces());
+    assert_eq!(from_fen.black.all_pieces(), initial.black.all_pieces());
+    assert_eq!(from_fen.white.queens, initial.white.queens);
+    assert_eq!(from_fen.black.king, initial.black.king);
+    assert_eq!(from_fen.player_to_move(), PlayerColor::White);
+}
+
+#[test]
+fn test_fen_round_trip() {
+    for fen in [
+        INITIAL_POSITION_FEN,
+        KIWIPETE_FEN,
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "rnbqkbnr/pppp1ppp/8/8/3Pp3/8/PPP1PPPP/RNBQKBNR b Kq d3 0 3",
+    ] {
+        assert_eq!(Position::from_fen(fen).unwrap().to_fen(), fen);
+    }
+}
+
+#[test]
+fn test_fen_optional_move_counters() {
+    let pos = Position::from_fen("4k3/8/8/8/8/8/8/4K3 b - -").unwrap();
+    assert_eq!(pos.to_fen(), "4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+}
+
+#[test]
+fn test_invalid_fens() {
+    for fen in [
+        "",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNX w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQxq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e4 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - x 1",
+        "rnbq1bnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQ - 0 1",
+        // Runs of empty squares of 0, or past the end of the rank.
+        "rnbqkbnr/pppppppp/8/8/99999999999999999999999999999/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/08/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/P8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        // Pawns on the first or last rank.
+        "P3k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/p3K3 b - - 0 1",
+        "p3k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/P3K3 w - - 0 1",
+    ] {
+        assert!(Position::from_fen(fen).is_err(), "{}", fen);
+    }
+}
+
+#[test]
+fn test_piece_at() {
+    let pos = Position::new();
+    let knight = pos.piece_at(G1).unwrap();
+    assert_eq!(knight.typpe, PieceType::Knight);
+    assert_eq!(knight.color, PlayerColor::White);
+    assert!(pos.piece_at(E4).is_none());
+    assert!(u64::nth(E2) & pos.white.pawns != 0);
+}
+
+#[test]
+fn test_make_move_updates_move_counters() {
+    let pos = Position::from_fen(INITIAL_POSITION_FEN).unwrap();
+    let pos = pos.make_move(
+        &BitboardMove {
+            from: G1,
+            to: F3,
+            sp_move_type: SpecialMoveType::RegularMove,
+        },
+        ChessPiece {
+            typpe: PieceType::Knight,
+            color: PlayerColor::White,
+        },
+    );
+    assert_eq!(
+        pos.to_fen(),
+        "rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1"
+    );
+}
+
+fn make_move_from_to(pos: &Position, from: u8, to: u8, sp_move_type: SpecialMoveType) -> Position {
+    let piece = pos.piece_at(from).unwrap();
+    pos.make_move(
+        &BitboardMove {
+            from,
+            to,
+            sp_move_type,
+        },
+        piece,
+    )
+}
+
+fn assert_hash_is_consistent(pos: &Position) {
+    let mut recomputed = *pos;
+    recomputed.compute_zobrist_hash();
+    assert_eq!(
+        pos.position_info.zobrist_hash,
+        recomputed.position_info.zobrist_hash
+    );
+}
+
+fn king_moves(pos: &Position) -> Vec<BitboardMove> {
+    let mut moves = MoveList::new();
+    KingBitboardMoveGenerator::generate_moves(
+        pos,
+        MoveGenOpts {
+            perspective: MoveGenPerspective::MovingPlayer,
+            mode: MoveGenMode::AllMoves,
+        },
+        &mut moves,
+    );
+    moves.iter().map(|mv| mv.bitboard_move()).collect()
+}
+
+#[test]
+fn test_castling_moves_rook_and_drops_rights() {
+    let pos = Position::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let short = make_move_from_to(&pos, E1, G1, SpecialMoveType::ShortCastle);
+    assert_eq!(short.to_fen(), "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1");
+    assert_hash_is_consistent(&short);
+
+    let long = make_move_from_to(&pos, E1, C1, SpecialMoveType::LongCastle);
+    assert_eq!(long.to_fen(), "r3k2r/8/8/8/8/8/8/2KR3R b kq - 1 1");
+    assert_hash_is_consistent(&long);
+}
+
+#[test]
+fn test_rook_moves_and_captures_drop_rights() {
+    let pos = Position::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let rook_moved = make_move_from_to(&pos, H1, H2, SpecialMoveType::RegularMove);
+    assert_eq!(rook_moved.to_fen(), "r3k2r/8/8/8/8/8/7R/R3K3 b Qkq - 1 1");
+    assert_hash_is_consistent(&rook_moved);
+
+    let rook_captured = make_move_from_to(&pos, A1, A8, SpecialMoveType::RegularMove);
+    assert_eq!(rook_captured.to_fen(), "R3k2r/8/8/8/8/8/8/4K2R b Kk - 0 1");
+    assert_hash_is_consistent(&rook_captured);
+}
+
+#[test]
+fn test_no_castling_after_king_moved() {
+    let pos = Position::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let castles = |pos: &Position| {
+        king_moves(pos)
+            .iter()
+            .filter(|mv| mv.sp_move_type != SpecialMoveType::RegularMove)
+            .count()
+    };
+    assert_eq!(castles(&pos), 2);
+
+    let mut moved = make_move_from_to(&pos, E1, E2, SpecialMoveType::RegularMove);
+    moved = make_move_from_to(&moved, E8, E7, SpecialMoveType::RegularMove);
+    moved = make_move_from_to(&moved, E2, E1, SpecialMoveType::RegularMove);
+    assert_eq!(moved.to_fen(), "r6r/4k3/8/8/8/8/8/R3K2R b - - 3 2");
+    assert_hash_is_consistent(&moved);
+    moved.pass_turn();
+    assert_eq!(castles(&moved), 0);
+}
+
+#[test]
+fn test_no_castling_through_blockers_or_attacks() {
+    let blocked = Position::from_fen("r3k2r/8/8/8/8/8/8/R2QK1NR w KQkq - 0 1").unwrap();
+    assert!(king_moves(&blocked)
+        .iter()
+        .all(|mv| mv.sp_move_type == SpecialMoveType::RegularMove));
+
+    // The rook on f2 only covers the short castling path.
+    let attacked = Position::from_fen("r3k2r/8/8/8/8/8/5r2/R3K2R w KQkq - 0 1").unwrap();
+    let castles: Vec<SpecialMoveType> = king_moves(&attacked)
+        .iter()
+        .map(|mv| mv.sp_move_type)
+        .filter(|sp_move_type| *sp_move_type != SpecialMoveType::RegularMove)
+        .collect();
+    assert_eq!(castles, vec![SpecialMoveType::LongCastle]);
+}
+
+fn legal_moves_from(pos: &Position, from: u8) -> Vec<BitboardMove> {
+    pos.legal_continuations()
+        .iter()
+        .filter(|mv| mv.from() == from)
+        .map(|mv| mv.bitboard_move())
+        .collect()
+}
+
+#[test]
+fn test_double_push_sets_en_passant_target() {
+    let pos = make_move_from_to(&Position::new(), E2, E4, SpecialMoveType::RegularMove);
+    assert_eq!(
+        pos.to_fen(),
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+    );
+    assert_hash_is_consistent(&pos);
+
+    // The target only lasts for one move.
+    let pos = make_move_from_to(&pos, G8, F6, SpecialMoveType::RegularMove);
+    assert_eq!(
+        pos.to_fen(),
+        "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2"
+    );
+    assert_hash_is_consistent(&pos);
+}
+
+#[test]
+fn test_en_passant_captures() {
+    let pos = Position::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+        .unwrap();
+    let en_passant_moves: Vec<BitboardMove> = legal_moves_from(&pos, E5)
+        .into_iter()
+        .filter(|mv| mv.sp_move_type == SpecialMoveType::EnPassantRight)
+        .collect();
+    assert_eq!(
+        en_passant_moves,
+        vec![BitboardMove {
+            from: E5,
+            to: F6,
+            sp_move_type: SpecialMoveType::EnPassantRight,
+        }]
+    );
+    // d5 was not the last pawn to advance two squares.
+    assert!(legal_moves_from(&pos, E5).iter().all(|mv| mv.to != D6));
+
+    let captured = make_move_from_to(&pos, E5, F6, SpecialMoveType::EnPassantRight);
+    assert_eq!(
+        captured.to_fen(),
+        "rnbqkbnr/ppp1p1pp/5P2/3p4/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3"
+    );
+    assert_hash_is_consistent(&captured);
+
+    let pos = Position::from_fen("4k3/8/8/8/3Pp3/8/8/4K3 b - d3 0 1").unwrap();
+    let captured = make_move_from_to(&pos, E4, D3, SpecialMoveType::EnPassantRight);
+    assert_eq!(captured.to_fen(), "4k3/8/8/8/8/3p4/8/4K3 w - - 0 2");
+    assert_hash_is_consistent(&captured);
+}
+
+#[test]
+fn test_en_passant_discovered_check_is_illegal() {
+    // Capturing would leave both pawns off the fifth rank and expose the king to the rook.
+    let pos = Position::from_fen("8/8/8/KPp4r/8/8/8/4k3 w - c6 0 1").unwrap();
+    assert!(legal_moves_from(&pos, B5)
+        .iter()
+        .all(|mv| mv.sp_move_type == SpecialMoveType::RegularMove));
+}
+
+#[test]
+fn test_legal_captures() {
+    let captures = |fen: &str| -> Vec<String> {
+        let mut result: Vec<String> = Position::from_fen(fen)
+            .unwrap()
+            .legal_captures()
+            .iter()
+            .map(|mv| mv.to_coordinates())
+            .collect();
+        result.sort();
+        result
+    };
+    assert!(captures(INITIAL_POSITION_FEN).is_empty());
+    // Kiwipete.
+    assert_eq!(
+        captures("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"),
+        vec!["d5e6", "e2a6", "e5d7", "e5f7", "e5g6", "f3f6", "f3h3", "g2h3"]
+    );
+    assert_eq!(captures("4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1"), vec!["d5e6"]);
+    // Quiet promotions count, quiet pawn pushes do not.
+    assert_eq!(
+        captures("2n1k3/1P6/8/8/8/8/6P1/4K3 w - - 0 1"),
+        vec!["b7b8b", "b7b8n", "b7b8q", "b7b8r", "b7c8b", "b7c8n", "b7c8q", "b7c8r"]
+    );
+}
+
+#[test]
+fn test_insufficient_material() {
+    let insufficient = |fen: &str| Position::from_fen(fen).unwrap().has_insufficient_material();
+    assert!(insufficient("4k3/8/8/8/8/8/8/4K3 w - - 0 1"));
+    assert!(insufficient("4k3/8/8/8/8/8/8/4KB2 w - - 0 1"));
+    assert!(insufficient("4k3/8/8/8/8/8/8/4KN2 w - - 0 1"));
+    assert!(insufficient("4kn2/8/8/8/8/8/8/4K3 b - - 0 1"));
+    // Bishops on c8, d1 and f1 all stand on light squares.
+    assert!(insufficient("2b1k3/8/8/8/8/8/8/4KB2 w - - 0 1"));
+    assert!(insufficient("2b1k3/8/8/8/8/8/8/3BKB2 w - - 0 1"));
+
+    assert!(!insufficient("1b2k3/8/8/8/8/8/8/4KB2 w - - 0 1"));
+    assert!(!insufficient("4k3/8/8/8/8/8/8/3NKN2 w - - 0 1"));
+    assert!(!insufficient("4kn2/8/8/8/8/8/8/4KB2 w - - 0 1"));
+    assert!(!insufficient("4k3/8/8/8/8/8/8/4K2R w - - 0 1"));
+    assert!(!insufficient("4k3/8/8/8/8/8/P7/4K3 w - - 0 1"));
+    assert!(!insufficient(INITIAL_POSITION_FEN));
+}
+
+#[test]
+fn test_fifty_move_draw() {
+    let position = Position::from_fen("4k3/8/8/8/8/8/8/4K2R w - - 99 80").unwrap();
+    assert!(!position.is_fifty_move_draw());
+    let after_rook_move = make_move_from_to(&position, H1, H2, SpecialMoveType::RegularMove);
+    assert!(after_rook_move.is_fifty_move_draw());
+}
+
+#[test]
+fn test_unmake_move_restores_the_position() {
+    // Castling, en passant, promotions and captures promoting.
+    for fen in [
+        INITIAL_POSITION_FEN,
+        KIWIPETE_FEN,
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    ] {
+        let original = Position::from_fen(fen).unwrap();
+        let mut position = original;
+        let mut undo_stack = UndoStack::new();
+        for mv in original.legal_continuations().iter() {
+            position.make_move_in_place(*mv, &mut undo_stack);
+            assert_eq!(position, original.play_move(*mv));
+            assert_hash_is_consistent(&position);
+            position.unmake_move(&mut undo_stack);
+            assert_eq!(position, original, "{} {:?}", fen, mv);
+        }
+        assert!(undo_stack.is_empty());
+    }
+}
+
+#[test]
+fn test_see() {
+    let see = |fen: &str, coordinates: &str| {
+        let position = Position::from_fen(fen).unwrap();
+        position.see(&position.find_legal_move(coordinates).unwrap())
+    };
+    // Undefended bishop, even pawn trade, pawns defended by the king and by the rook on h8,
+    // which the pawn on g2 takes back.
+    assert_eq!(see(KIWIPETE_FEN, "e2a6"), 325);
+    assert_eq!(see(KIWIPETE_FEN, "d5e6"), 0);
+    assert_eq!(see(KIWIPETE_FEN, "f3h3"), 105 - 900 + 500);
+    assert_eq!(see(KIWIPETE_FEN, "e5f7"), 105 - 310);
+    // Quiet moves only lose the moving piece, if anything.
+    assert_eq!(see(KIWIPETE_FEN, "a2a3"), 0);
+    assert_eq!(see(KIWIPETE_FEN, "f3f5"), -900);
+
+    // The rook on e1 backs up the one on e2 through it.
+    assert_eq!(see("4r1k1/8/8/4p3/8/8/4R3/4R1K1 w - - 0 1", "e2e5"), 105);
+    assert_eq!(
+        see("4r1k1/8/8/4p3/8/8/4R3/6K1 w - - 0 1", "e2e5"),
+        105 - 500
+    );
+    // The king cannot take back, the rook on f1 defends f7 once the queen has left f3.
+    assert_eq!(see("6k1/5p2/8/8/8/5Q2/8/5RK1 w - - 0 1", "f3f7"), 105);
+    assert_eq!(see("6k1/5p2/8/8/8/8/8/5QK1 w - - 0 1", "f1f7"), 105 - 900);
+
+    // Promotions win the promoted piece, and lose it when it is taken back.
+    assert_eq!(see("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"), 900 - 105);
+    assert_eq!(see("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"), -105);
+    // En passant.
+    assert_eq!(see("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1", "e4d3"), 105);
+}
+
+#[test]
+fn test_in_check() {
+    assert!(!Position::new().in_check());
+    assert!(Position::from_fen("4r2k/8/8/8/8/8/R7/4K3 w - - 0 1")
+        .unwrap()
+        .in_check());
+    // The knight on f3 checks the king on e1, not the one on h8.
+    let position = Position::from_fen("7k/8/8/8/8/5n2/8/4K3 b - - 0 1").unwrap();
+    assert!(!position.in_check());
+    assert!(Position::from_fen("7k/8/8/8/8/5n2/8/4K3 w - - 0 1")
+        .unwrap()
+        .in_check());
+}
+
+#[test]
+fn test_null_move() {
+    // The en passant target on f6 expires.
+    let position =
+        Position::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+            .unwrap();
+    let mut after_null = position;
+    after_null.make_null_move();
+    assert_eq!(
+        after_null.to_fen(),
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR b KQkq - 1 3"
+    );
+    assert_hash_is_consistent(&after_null);
+    after_null.make_null_move();
+    assert_eq!(after_null.position_info.fullmove_number, 4);
+    assert_hash_is_consistent(&after_null);
+
+    assert!(position.has_non_pawn_material());
+    assert!(!Position::from_fen("4k3/4p3/8/8/8/8/4P3/4K3 w - - 0 1")
+        .unwrap()
+        .has_non_pawn_material());
+}
+
+#[test]
+fn test_pawn_hash_only_depends_on_pawns() {
+    let position = Position::from_fen(KIWIPETE_FEN).unwrap();
+    let knight_move = position.find_legal_move("e5d3").unwrap();
+    let after_knight_move = position.play_move(knight_move);
+    assert_eq!(after_knight_move.pawn_hash(), position.pawn_hash());
+    assert_ne!(
+        after_knight_move.position_info.zobrist_hash,
+        position.position_info.zobrist_hash
+    );
+    let pawn_move = position.find_legal_move("a2a3").unwrap();
+    assert_ne!(
+        position.play_move(pawn_move).pawn_hash(),
+        position.pawn_hash()
+    );
+    // The same pawns in a different position.
+    let same_pawns = Position::from_fen("4k3/p1ppqpb1/4p1p1/3P4/1p2P3/7p/PPP2PPP/4K3 b - - 0 1");
+    assert_eq!(same_pawns.unwrap().pawn_hash(), position.pawn_hash());
+}
+
+#[test]
+fn test_castling_rights_need_the_king_and_rook_at_home() {
+    let pos = Position::from_fen("4k3/8/8/8/8/8/8/4K3 w KQkq - 0 1").unwrap();
+    assert_eq!(pos.to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+    assert!(pos.find_legal_move("e1g1").is_none());
+    // Only the rooks that are at home keep their rights.
+    let pos = Position::from_fen("r3k3/8/8/8/8/8/8/4K2R w KQkq - 0 1").unwrap();
+    assert_eq!(pos.to_fen(), "r3k3/8/8/8/8/8/8/4K2R w Kq - 0 1");
+}
+
+#[test]
+fn test_halfmove_clock_does_not_overflow() {
+    let pos = Position::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 65535 1").unwrap();
+    let pos = pos.make_move(
+        &BitboardMove {
+            from: E1,
+            to: E2,
+            sp_move_type: SpecialMoveType::RegularMove,
+        },
+        ChessPiece {
+            typpe: PieceType::King,
+            color: PlayerColor::White,
+        },
+    );
+    assert_eq!(pos.position_info.halfmove_clock, u16::MAX);
+}
@@ -10,8 +10,8 @@ static INIT: Once = Once::new();
 pub struct ZobristTable {
     pub table: [[[u64; 64]; 2]; 6], // PieceType * PlayerColor * Square
     pub castling_rights: [u64; 4],  // Castling rights
-    // en_passant: [u64; 64],       // En passant square (file)
-    pub black_to_move: u64, // Side to move
+    pub en_passant: [u64; 64],      // En passant target square
+    pub black_to_move: u64,         // Side to move
 }
 
 impl ZobristTable {
@@ -28,7 +28,7 @@ impl ZobristTable {
         let mut rng = thread_rng();
         let mut table = [[[0; 64]; 2]; 6];
         let mut castling_rights = [0; 4];
-        // let mut en_passant = [0; 64];
+        let mut en_passant = [0; 64];
         let black_to_move = rng.gen::<u64>();
 
         for piece_type in PieceType::iter() {
@@ -43,14 +43,14 @@ impl ZobristTable {
             castling_rights[kind as usize] = rng.gen::<u64>();
         }
 
-        // for i in 0..64 {
-        //     en_passant[i] = rng.gen::<u64>();
-        // }
+        for i in 0..64 {
+            en_passant[i] = rng.gen::<u64>();
+        }
 
         Self {
             table,
             castling_rights,
-            // en_passant,
+            en_passant,
             black_to_move,
         }
     }
@@ -8,25 +8,22 @@ use crate::strum::IntoEnumIterator;
 use super::PositionEvaluator;
 use crate::chess::bitboard::{BitArraySize, PlayerBitboard, SpecialMoveType};
 use crate::move_gen::MoveGenPerspective;
+use crate::search::score::mated_score;
 use std::collections::HashMap;
 
+// Scores checkmated positions, from white's point of view, and 0 for everything else,
+// stalemate included. Not part of the default pipeline: looking for legal moves at every leaf
+// is expensive, and the search already scores checkmates along with their distance to the
+// root.
 pub struct CheckmateEvaluator {}
 
 impl PositionEvaluator for CheckmateEvaluator {
     fn evaluate(&self, position: &Position) -> i32 {
-        // if !position.legal_continuations().is_empty() {
-        //     let moving_player = position.player_to_move();
-        //     if position.can_king_be_captured(MoveGenPerspective::MovingPlayer) {
-        //         1000000
-        //     } else {
-        //         0 // Stalemate.
-        //     }
-        // } else if position.can_king_be_captured(MoveGenPerspective::MovingPlayer) {
-        //     // Small demotion if king is in check.
-        //     -60
-        // } else {
-        //     0
-        // }
-        0
+        if !position.can_king_be_captured(MoveGenPerspective::MovingPlayer)
+            || !position.legal_continuations().is_empty()
+        {
+            return 0;
+        }
+        mated_score(position.player_to_move(), 0)
     }
 }
@@ -0,0 +1,27 @@
+use crate::chess::position::Position;
+use crate::chess::PieceType;
+use crate::strum::IntoEnumIterator;
+
+// How much each piece type counts towards the middlegame, indexed by PieceType. The pieces
+// of the initial position add up to MAX_PHASE.
+const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
+pub const MAX_PHASE: i32 = 24;
+
+// Game phase from the non-pawn material left on the board: MAX_PHASE in the middlegame, down
+// to 0 when only kings and pawns remain. Promotions cannot raise it above MAX_PHASE.
+pub fn game_phase(position: &Position) -> i32 {
+    let (white, black) = (position.white, position.black);
+    PieceType::iter()
+        .map(|typpe| {
+            let count = (white.pieces(typpe) | black.pieces(typpe)).count_ones() as i32;
+            count * PHASE_WEIGHTS[typpe as usize]
+        })
+        .sum::<i32>()
+        .min(MAX_PHASE)
+}
+
+// Tapered evaluation: blends the middlegame score |mg| and the endgame score |eg| by |phase|,
+// so that the score changes smoothly as pieces are traded.
+pub fn taper(mg: i32, eg: i32, phase: i32) -> i32 {
+    (mg * phase + eg * (MAX_PHASE - phase)) / MAX_PHASE
+}
@@ -0,0 +1,75 @@
+use super::game_phase::{game_phase, taper, MAX_PHASE};
+use super::material_evaluator::{MaterialEvaluator, ENDGAME_PIECE_VALUES, PIECE_VALUES};
+use super::piece_coordinate_evaluator::PieceCoordinateEvaluator;
+use super::PositionEvaluator;
+use crate::chess::position::{Position, INITIAL_POSITION_FEN};
+
+fn phase(fen: &str) -> i32 {
+    game_phase(&Position::from_fen(fen).unwrap())
+}
+
+#[test]
+fn test_game_phase() {
+    assert_eq!(phase(INITIAL_POSITION_FEN), MAX_PHASE);
+    // A queen and a rook each.
+    assert_eq!(phase("3qk2r/8/8/8/8/8/8/R2QK3 w - - 0 1"), 12);
+    assert_eq!(phase("4k3/pppp4/8/8/8/8/4PPPP/4K3 w - - 0 1"), 0);
+    // Promotions do not go past the middlegame.
+    assert_eq!(
+        phase("rnbqkbnr/pppppppp/8/8/8/8/8/QQQQKBNR w - - 0 1"),
+        MAX_PHASE
+    );
+}
+
+#[test]
+fn test_taper() {
+    assert_eq!(taper(100, 200, MAX_PHASE), 100);
+    assert_eq!(taper(100, 200, 0), 200);
+    assert_eq!(taper(100, 200, MAX_PHASE / 2), 150);
+}
+
+#[test]
+fn test_material_is_tapered() {
+    let evaluate = |fen| MaterialEvaluator::new().evaluate(&Position::from_fen(fen).unwrap());
+    assert_eq!(evaluate(INITIAL_POSITION_FEN), 0);
+    // An extra pawn in the middlegame, then in a pawn endgame.
+    assert_eq!(
+        evaluate("rnbqkbnr/ppp1pppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+        PIECE_VALUES[0]
+    );
+    assert_eq!(
+        evaluate("4k3/4p3/8/8/8/8/3PP3/4K3 w - - 0 1"),
+        ENDGAME_PIECE_VALUES[0]
+    );
+}
+
+#[test]
+fn test_king_shelters_in_the_middlegame_and_centralizes_in_the_endgame() {
+    let evaluate =
+        |fen| PieceCoordinateEvaluator::new().evaluate(&Position::from_fen(fen).unwrap());
+    let castled = evaluate("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQNBKR w kq - 0 1");
+    let centralized = evaluate("rnbqkbnr/pppppppp/8/8/4K3/8/PPPPPPPP/RNBQNB1R w kq - 0 1");
+    assert!(castled > centralized);
+
+    let castled = evaluate("4k3/8/8/8/8/8/8/6K1 w - - 0 1");
+    let centralized = evaluate("4k3/8/8/8/4K3/8/8/8 w - - 0 1");
+    assert!(centralized > castled);
+}
+
+#[test]
+fn test_pieces_have_their_own_endgame_squares() {
+    let evaluate =
+        |fen| PieceCoordinateEvaluator::new().evaluate(&Position::from_fen(fen).unwrap());
+    // The rook goes to the seventh rank.
+    assert!(
+        evaluate("4k3/R7/8/8/8/8/8/4K3 w - - 0 1") > evaluate("4k3/8/8/8/8/R7/8/4K3 w - - 0 1")
+    );
+    // The bishop prefers the long diagonal to its development square.
+    assert!(
+        evaluate("4k3/8/8/8/8/8/1B6/4K3 w - - 0 1") > evaluate("4k3/8/8/8/8/8/2B5/4K3 w - - 0 1")
+    );
+    // The knight leaves the rim.
+    assert!(
+        evaluate("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1") > evaluate("4k3/8/8/8/8/8/8/N3K3 w - - 0 1")
+    );
+}
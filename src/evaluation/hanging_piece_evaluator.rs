@@ -0,0 +1,67 @@
+use crate::chess::bitboard::{BitArraySize, SpecialMoveType, EMPTY_BOARD};
+use crate::chess::position::Position;
+use crate::chess::{PieceType, PlayerColor};
+use crate::move_gen::attacks::attackers_to;
+use crate::move_gen::move_list::Move;
+use crate::strum::IntoEnumIterator;
+
+use super::PositionEvaluator;
+
+// Penalizes the player to move for the pieces it cannot keep. A piece hangs when the exchange
+// the enemy can start on it wins material. The player to move can take one hanging piece out
+// of danger, and the search sees that move, but not a second one: the position is scored as
+// if the second most valuable loss had happened.
+#[derive(Default)]
+pub struct HangingPieceEvaluator {}
+
+impl HangingPieceEvaluator {
+    pub fn new() -> Self {
+        HangingPieceEvaluator {}
+    }
+
+    // Material the waiting player would win by capturing each piece of the player to move,
+    // largest first. Only the two largest amounts are kept.
+    fn largest_losses(position: &Position) -> [i32; 2] {
+        let mut enemy_to_move = *position;
+        enemy_to_move.pass_turn();
+        let (allies, enemies) = (position.pieces_to_move(), position.enemy_pieces());
+        let enemy_color = position.waiting_player();
+        let occupancy = allies.all_pieces() | enemies.all_pieces();
+        let mut losses = [0; 2];
+        for typpe in PieceType::iter().filter(|typpe| *typpe != PieceType::King) {
+            let mut piece_set = *allies.pieces(typpe);
+            while piece_set != EMPTY_BOARD {
+                let sq_id = piece_set.trailing_zeros() as u8;
+                piece_set ^= u64::nth(sq_id);
+                // The king only takes undefended pieces, which the exchange evaluation of its
+                // first capture does not check: it is left out.
+                let attackers =
+                    attackers_to(sq_id, enemies, enemy_color, occupancy) & !enemies.king;
+                let Some(attacker_type) = PieceType::iter()
+                    .find(|attacker_type| *enemies.pieces(*attacker_type) & attackers != 0)
+                else {
+                    continue;
+                };
+                let from = (*enemies.pieces(attacker_type) & attackers).trailing_zeros() as u8;
+                let capture = Move::new(from, sq_id, SpecialMoveType::RegularMove, attacker_type);
+                let loss = enemy_to_move.see(&capture);
+                if loss > losses[0] {
+                    losses = [loss, losses[0]];
+                } else if loss > losses[1] {
+                    losses[1] = loss;
+                }
+            }
+        }
+        losses
+    }
+}
+
+impl PositionEvaluator for HangingPieceEvaluator {
+    fn evaluate(&self, position: &Position) -> i32 {
+        let penalty = Self::largest_losses(position)[1];
+        match position.player_to_move() {
+            PlayerColor::White => -penalty,
+            PlayerColor::Black => penalty,
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use super::hanging_piece_evaluator::HangingPieceEvaluator;
+use super::PositionEvaluator;
+use crate::chess::position::Position;
+
+fn evaluate(fen: &str) -> i32 {
+    HangingPieceEvaluator::new().evaluate(&Position::from_fen(fen).unwrap())
+}
+
+#[test]
+fn test_forked_pieces() {
+    // The pawn on e6 forks both knights: one of them is lost.
+    assert_eq!(evaluate("4k3/8/4p3/3N1N2/8/8/8/4K3 w - - 0 1"), -310);
+    assert_eq!(evaluate("4k3/8/3n1n2/4P3/8/8/8/4K3 b - - 0 1"), 310);
+    // The pawn on e4 takes back.
+    assert_eq!(
+        evaluate("4k3/8/4p3/3N1N2/4P3/8/8/4K3 w - - 0 1"),
+        -(310 - 105)
+    );
+    // Black has nothing hanging, the fork is only a threat for white to parry.
+    assert_eq!(evaluate("4k3/8/4p3/3N1N2/8/8/8/4K3 b - - 0 1"), 0);
+}
+
+#[test]
+fn test_a_single_hanging_piece_is_left_to_the_search() {
+    assert_eq!(evaluate("4k3/8/4p3/3N4/8/8/8/4K3 w - - 0 1"), 0);
+    assert_eq!(evaluate(crate::chess::position::INITIAL_POSITION_FEN), 0);
+}
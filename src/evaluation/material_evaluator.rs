@@ -5,48 +5,74 @@ use crate::chess::PlayerColor;
 
 use crate::strum::IntoEnumIterator;
 
+use super::game_phase::{game_phase, taper};
 use super::PositionEvaluator;
 use crate::chess::bitboard::{BitArraySize, PlayerBitboard, SpecialMoveType};
 use std::collections::HashMap;
 
+// Centipawn value of each piece type, indexed by PieceType. The king is never traded. These
+// are the middlegame values, also used to weigh exchanges.
+pub const PIECE_VALUES: [i32; 6] = [105, 310, 325, 500, 900, 0];
+// In the endgame, pawns are closer to promoting and rooks have open files, while knights
+// lack targets.
+pub const ENDGAME_PIECE_VALUES: [i32; 6] = [130, 290, 320, 530, 940, 0];
+
 pub struct MaterialEvaluator {
     piece_values: HashMap<PieceType, i32>,
+    endgame_piece_values: HashMap<PieceType, i32>,
 }
 
 impl MaterialEvaluator {
     pub fn new() -> Self {
         let mut piece_values = HashMap::new();
-        piece_values.insert(PieceType::Pawn, 105);
-        piece_values.insert(PieceType::Knight, 310);
-        piece_values.insert(PieceType::Bishop, 325);
-        piece_values.insert(PieceType::Rook, 500);
-        piece_values.insert(PieceType::Queen, 900);
-        piece_values.insert(PieceType::King, 0);
+        let mut endgame_piece_values = HashMap::new();
+        for piece_type in PieceType::iter() {
+            piece_values.insert(piece_type, PIECE_VALUES[piece_type as usize]);
+            endgame_piece_values.insert(piece_type, ENDGAME_PIECE_VALUES[piece_type as usize]);
+        }
 
         MaterialEvaluator {
             piece_values: piece_values,
+            endgame_piece_values,
         }
     }
 
-    fn count_pieces_of_type(&self, mut piece_set: BitB64, piece_type: PieceType) -> i32 {
+    // The middlegame value, which weighs exchanges.
+    pub fn piece_value(&self, piece_type: PieceType) -> i32 {
+        self.piece_values[&piece_type]
+    }
+
+    fn count_pieces_of_type(
+        &self,
+        mut piece_set: BitB64,
+        piece_values: &HashMap<PieceType, i32>,
+        piece_type: PieceType,
+    ) -> i32 {
         let mut result = 0;
         while piece_set != 0 {
             let piece_id = piece_set.trailing_zeros() as u8;
             piece_set ^= u64::nth(piece_id);
-            result += self.piece_values[&piece_type];
+            result += piece_values[&piece_type];
         }
         result
     }
-}
 
-impl PositionEvaluator for MaterialEvaluator {
-    fn evaluate(&self, position: &Position) -> i32 {
+    // White's material minus black's, valued with |piece_values|.
+    fn material_balance(&self, position: &Position, piece_values: &HashMap<PieceType, i32>) -> i32 {
         let mut score = 0;
         let (white, black) = (position.white, position.black);
         for piece_type in PieceType::iter() {
-            score += self.count_pieces_of_type(*white.pieces(piece_type), piece_type);
-            score -= self.count_pieces_of_type(*black.pieces(piece_type), piece_type);
+            score += self.count_pieces_of_type(*white.pieces(piece_type), piece_values, piece_type);
+            score -= self.count_pieces_of_type(*black.pieces(piece_type), piece_values, piece_type);
         }
-        return score;
+        score
+    }
+}
+
+impl PositionEvaluator for MaterialEvaluator {
+    fn evaluate(&self, position: &Position) -> i32 {
+        let middlegame = self.material_balance(position, &self.piece_values);
+        let endgame = self.material_balance(position, &self.endgame_piece_values);
+        taper(middlegame, endgame, game_phase(position))
     }
 }
@@ -1,13 +1,21 @@
+pub mod checkmate_evaluator;
+pub mod game_phase;
+pub mod hanging_piece_evaluator;
 pub mod material_evaluator;
+pub mod pawn_hash_table;
+pub mod pawn_structure_evaluator;
 pub mod piece_coordinate_evaluator;
-pub mod checkmate_evaluator;
-
-
 
 pub mod search_evaluator;
+
+mod game_phase_test;
+mod hanging_piece_evaluator_test;
+mod pawn_structure_evaluator_test;
+mod search_evaluator_test;
 use crate::chess::position::Position;
 
-pub trait PositionEvaluator {
+// Evaluators are shared by the threads of a search.
+pub trait PositionEvaluator: Send + Sync {
     fn evaluate(&self, position: &Position) -> i32;
 }
 
@@ -15,6 +23,20 @@ pub struct PositionEvaluationPipeline {
     pub evaluators: Vec<Box<dyn PositionEvaluator>>,
 }
 
+impl PositionEvaluationPipeline {
+    // The leaf evaluators used by the engine when searching.
+    pub fn new_default() -> Self {
+        PositionEvaluationPipeline {
+            evaluators: vec![
+                Box::new(material_evaluator::MaterialEvaluator::new()),
+                Box::new(pawn_structure_evaluator::PawnStructureEvaluator::new()),
+                Box::new(piece_coordinate_evaluator::PieceCoordinateEvaluator::new()),
+                Box::new(hanging_piece_evaluator::HangingPieceEvaluator::new()),
+            ],
+        }
+    }
+}
+
 impl PositionEvaluator for PositionEvaluationPipeline {
     fn evaluate(&self, position: &Position) -> i32 {
         let mut sum = 0;
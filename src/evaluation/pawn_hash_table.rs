@@ -0,0 +1,75 @@
+use crate::chess::bitboard::BitB64;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub const DEFAULT_PAWN_TABLE_ENTRIES: usize = 1 << 14;
+
+// What the pawns alone say about a position. Scores are from white's point of view.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PawnEntry {
+    pub middlegame: i32,
+    pub endgame: i32,
+    // Passed pawns of both colors.
+    pub passed_pawns: BitB64,
+}
+
+// As in the transposition table, the key is stored xored with the data, so that a slot
+// written by two threads at once reads as another position's. An empty slot reads as the
+// entry of the position without pawns, which is all zeros anyway.
+#[derive(Default)]
+struct Slot {
+    checked_key: AtomicU64,
+    scores: AtomicU64,
+    passed_pawns: AtomicU64,
+}
+
+// Cache of pawn structure evaluations, indexed by Position::pawn_hash(). Pawns move rarely,
+// so most positions of a search share their pawn structure with many others. Each slot holds
+// the last entry stored in it.
+pub struct PawnHashTable {
+    slots: Vec<Slot>,
+}
+
+impl PawnHashTable {
+    // |num_entries| is rounded up to a power of two.
+    pub fn new(num_entries: usize) -> Self {
+        PawnHashTable {
+            slots: (0..num_entries.next_power_of_two())
+                .map(|_| Slot::default())
+                .collect(),
+        }
+    }
+
+    fn slot(&self, key: u64) -> &Slot {
+        &self.slots[key as usize & (self.slots.len() - 1)]
+    }
+
+    pub fn probe(&self, key: u64) -> Option<PawnEntry> {
+        let slot = self.slot(key);
+        let scores = slot.scores.load(Ordering::Relaxed);
+        let passed_pawns = slot.passed_pawns.load(Ordering::Relaxed);
+        if slot.checked_key.load(Ordering::Relaxed) ^ scores ^ passed_pawns != key {
+            return None;
+        }
+        Some(PawnEntry {
+            middlegame: scores as i32,
+            endgame: (scores >> 32) as i32,
+            passed_pawns,
+        })
+    }
+
+    pub fn store(&self, key: u64, entry: &PawnEntry) {
+        let slot = self.slot(key);
+        let scores = entry.middlegame as u32 as u64 | (entry.endgame as u32 as u64) << 32;
+        slot.scores.store(scores, Ordering::Relaxed);
+        slot.passed_pawns
+            .store(entry.passed_pawns, Ordering::Relaxed);
+        slot.checked_key
+            .store(key ^ scores ^ entry.passed_pawns, Ordering::Relaxed);
+    }
+}
+
+impl Default for PawnHashTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_PAWN_TABLE_ENTRIES)
+    }
+}
@@ -0,0 +1,213 @@
+use crate::chess::bitboard::{BitArraySize, BitB64, EMPTY_BOARD, FULL_BOARD};
+use crate::chess::position::Position;
+use crate::chess::PlayerColor;
+use crate::move_gen::attacks::pawn_attacks;
+
+use super::game_phase::{game_phase, taper};
+use super::pawn_hash_table::{PawnEntry, PawnHashTable};
+use super::PositionEvaluator;
+
+// Scores are (middlegame, endgame) pairs, for each pawn concerned.
+const DOUBLED_PAWN: (i32, i32) = (-10, -20);
+const ISOLATED_PAWN: (i32, i32) = (-10, -15);
+const BACKWARD_PAWN: (i32, i32) = (-8, -10);
+// Defended by a pawn, or side by side with one.
+const CONNECTED_PAWN: (i32, i32) = (8, 8);
+// By rank, counted from the pawn's side of the board.
+const PASSED_PAWN: [(i32, i32); 8] = [
+    (0, 0),
+    (5, 10),
+    (5, 15),
+    (10, 25),
+    (25, 45),
+    (45, 80),
+    (70, 130),
+    (0, 0),
+];
+// The passed pawn terms that depend on the other pieces are multiplied by these, by rank: a
+// free path or a close king matter more the closer the pawn is to promoting.
+const PASSED_PAWN_RANK_WEIGHTS: [i32; 8] = [0, 0, 0, 1, 2, 4, 6, 0];
+// No piece of either color stands on the way to promotion.
+const FREE_PATH: (i32, i32) = (2, 8);
+// Endgame only, per square the enemy king is farther than ours from the square in front of
+// the pawn.
+const KING_PROXIMITY: i32 = 3;
+
+const FILE_A: BitB64 = 0x0101_0101_0101_0101;
+const FILE_H: BitB64 = FILE_A << 7;
+
+fn file_mask(file: u8) -> BitB64 {
+    FILE_A << file
+}
+
+fn adjacent_files_mask(file: u8) -> BitB64 {
+    let mask = file_mask(file);
+    ((mask << 1) & !FILE_A) | ((mask >> 1) & !FILE_H)
+}
+
+// The ranks in front of |rank|, from the point of view of |color|.
+fn ranks_ahead_mask(color: PlayerColor, rank: u8) -> BitB64 {
+    match color {
+        PlayerColor::White if rank < 7 => FULL_BOARD << (8 * (rank + 1)),
+        PlayerColor::Black if rank > 0 => FULL_BOARD >> (8 * (8 - rank)),
+        _ => EMPTY_BOARD,
+    }
+}
+
+// Rank counted from |color|'s side of the board.
+fn relative_rank(color: PlayerColor, sq_id: u8) -> usize {
+    match color {
+        PlayerColor::White => (sq_id / 8) as usize,
+        PlayerColor::Black => 7 - (sq_id / 8) as usize,
+    }
+}
+
+// The square a pawn of |color| on |sq_id| moves to. None on the last rank, where pawns only
+// stand in the unchecked positions of the web UI.
+fn stop_square(color: PlayerColor, sq_id: u8) -> Option<u8> {
+    match color {
+        PlayerColor::White => sq_id.checked_add(8).filter(|sq_id| *sq_id < 64),
+        PlayerColor::Black => sq_id.checked_sub(8),
+    }
+}
+
+// King moves between two squares on an empty board.
+fn distance(from: u8, to: u8) -> i32 {
+    let rank_distance = ((from / 8) as i32 - (to / 8) as i32).abs();
+    let file_distance = ((from % 8) as i32 - (to % 8) as i32).abs();
+    rank_distance.max(file_distance)
+}
+
+// Scores doubled, isolated, backward, connected and passed pawns. What only depends on the
+// pawns is kept in a pawn hash table; passed pawns are then scaled by how free their path is
+// and by how close the kings are, which depends on the other pieces.
+pub struct PawnStructureEvaluator {
+    pawn_table: PawnHashTable,
+}
+
+impl PawnStructureEvaluator {
+    pub fn new() -> Self {
+        PawnStructureEvaluator {
+            pawn_table: PawnHashTable::default(),
+        }
+    }
+
+    // The part of the evaluation that only depends on the pawns.
+    pub fn pawn_structure(position: &Position) -> PawnEntry {
+        let mut entry = PawnEntry::default();
+        for color in [PlayerColor::White, PlayerColor::Black] {
+            let (allies, enemies) = match color {
+                PlayerColor::White => (position.white.pawns, position.black.pawns),
+                PlayerColor::Black => (position.black.pawns, position.white.pawns),
+            };
+            let sign = match color {
+                PlayerColor::White => 1,
+                PlayerColor::Black => -1,
+            };
+            let mut add = |(middlegame, endgame): (i32, i32)| {
+                entry.middlegame += sign * middlegame;
+                entry.endgame += sign * endgame;
+            };
+            let mut pawns = allies;
+            while pawns != EMPTY_BOARD {
+                let sq_id = pawns.trailing_zeros() as u8;
+                pawns ^= u64::nth(sq_id);
+                let Some(stop) = stop_square(color, sq_id) else {
+                    continue;
+                };
+                let (file, rank) = (sq_id % 8, sq_id / 8);
+                let ahead = ranks_ahead_mask(color, rank);
+                let adjacent_files = adjacent_files_mask(file);
+                let doubled = allies & file_mask(file) & ahead != EMPTY_BOARD;
+                if doubled {
+                    add(DOUBLED_PAWN);
+                }
+                if allies & adjacent_files == EMPTY_BOARD {
+                    add(ISOLATED_PAWN);
+                } else if allies & adjacent_files & !ahead == EMPTY_BOARD
+                    && pawn_attacks(color, stop) & enemies != EMPTY_BOARD
+                {
+                    // Every neighbor is ahead, and the pawn cannot advance to them.
+                    add(BACKWARD_PAWN);
+                }
+                let defended = pawn_attacks(PlayerColor::other(color), sq_id) & allies;
+                let side_by_side = allies & adjacent_files & (0xff << (8 * rank));
+                if defended | side_by_side != EMPTY_BOARD {
+                    add(CONNECTED_PAWN);
+                }
+                // Only the front pawn of doubled pawns can be passed.
+                if !doubled && enemies & (file_mask(file) | adjacent_files) & ahead == EMPTY_BOARD {
+                    add(PASSED_PAWN[relative_rank(color, sq_id)]);
+                    entry.passed_pawns |= u64::nth(sq_id);
+                }
+            }
+        }
+        entry
+    }
+
+    // Bonus of the passed pawns for their free path and for the proximity of the kings, from
+    // white's point of view.
+    fn passed_pawn_bonus(position: &Position, passed_pawns: BitB64) -> (i32, i32) {
+        let occupancy = position.white.all_pieces() | position.black.all_pieces();
+        let (mut middlegame, mut endgame) = (0, 0);
+        let mut pawns = passed_pawns;
+        while pawns != EMPTY_BOARD {
+            let sq_id = pawns.trailing_zeros() as u8;
+            pawns ^= u64::nth(sq_id);
+            let color = match position.white.pawns & u64::nth(sq_id) != EMPTY_BOARD {
+                true => PlayerColor::White,
+                false => PlayerColor::Black,
+            };
+            let (allies, enemies) = match color {
+                PlayerColor::White => (&position.white, &position.black),
+                PlayerColor::Black => (&position.black, &position.white),
+            };
+            let weight = PASSED_PAWN_RANK_WEIGHTS[relative_rank(color, sq_id)];
+            let (mut pawn_middlegame, mut pawn_endgame) = (0, 0);
+            let path = file_mask(sq_id % 8) & ranks_ahead_mask(color, sq_id / 8);
+            if path & occupancy == EMPTY_BOARD {
+                pawn_middlegame += weight * FREE_PATH.0;
+                pawn_endgame += weight * FREE_PATH.1;
+            }
+            let Some(stop) = stop_square(color, sq_id) else {
+                continue;
+            };
+            let king_distance = |king: BitB64| distance(king.trailing_zeros() as u8, stop);
+            if allies.king != EMPTY_BOARD && enemies.king != EMPTY_BOARD {
+                pawn_endgame += weight
+                    * KING_PROXIMITY
+                    * (king_distance(enemies.king) - king_distance(allies.king));
+            }
+            let sign = match color {
+                PlayerColor::White => 1,
+                PlayerColor::Black => -1,
+            };
+            middlegame += sign * pawn_middlegame;
+            endgame += sign * pawn_endgame;
+        }
+        (middlegame, endgame)
+    }
+}
+
+impl Default for PawnStructureEvaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PositionEvaluator for PawnStructureEvaluator {
+    fn evaluate(&self, position: &Position) -> i32 {
+        let key = position.pawn_hash();
+        let entry = self.pawn_table.probe(key).unwrap_or_else(|| {
+            let entry = Self::pawn_structure(position);
+            self.pawn_table.store(key, &entry);
+            entry
+        });
+        let (middlegame, endgame) = Self::passed_pawn_bonus(position, entry.passed_pawns);
+        taper(
+            entry.middlegame + middlegame,
+            entry.endgame + endgame,
+            game_phase(position),
+        )
+    }
+}
@@ -0,0 +1,116 @@
+use super::pawn_hash_table::{PawnEntry, PawnHashTable};
+use super::pawn_structure_evaluator::PawnStructureEvaluator;
+use super::PositionEvaluator;
+use crate::chess::bitboard::{BitArraySize, A1, A6, C4, EMPTY_BOARD, H8};
+use crate::chess::position::{Position, INITIAL_POSITION_FEN};
+
+fn pawn_structure(fen: &str) -> PawnEntry {
+    PawnStructureEvaluator::pawn_structure(&Position::from_fen(fen).unwrap())
+}
+
+fn evaluate(fen: &str) -> i32 {
+    PawnStructureEvaluator::new().evaluate(&Position::from_fen(fen).unwrap())
+}
+
+#[test]
+fn test_doubled_and_isolated_pawns() {
+    // c2 is doubled, both pawns are isolated, and c3 is passed on the third rank.
+    assert_eq!(
+        pawn_structure("4k3/8/8/8/8/2P5/2P5/4K3 w - - 0 1"),
+        PawnEntry {
+            middlegame: -10 - 2 * 10 + 5,
+            endgame: -20 - 2 * 15 + 15,
+            passed_pawns: u64::nth(C4 - 8),
+        }
+    );
+    assert_eq!(pawn_structure(INITIAL_POSITION_FEN), PawnEntry::default());
+}
+
+#[test]
+fn test_backward_connected_and_passed_pawns() {
+    // d3 is backward: c4 is ahead of it, and e5 guards d4. c4 is passed and defended by d3.
+    // Black's e5 is isolated.
+    assert_eq!(
+        pawn_structure("4k3/8/8/4p3/2P5/3P4/8/4K3 w - - 0 1"),
+        PawnEntry {
+            middlegame: 8 + 10 - 8 + 10,
+            endgame: 8 + 25 - 10 + 15,
+            passed_pawns: u64::nth(C4),
+        }
+    );
+}
+
+#[test]
+fn test_passed_pawns_scale_with_their_path_and_the_kings() {
+    // Isolated and passed on the sixth rank, in a pawn endgame: (80 - 15) before scaling.
+    let free_path = 4 * 8;
+    // The kings are a1 and h8 from a7: ours is one square closer.
+    assert_eq!(
+        evaluate("7k/8/P7/8/8/8/8/K7 w - - 0 1"),
+        80 - 15 + free_path + 4 * 3
+    );
+    // Theirs is six squares closer.
+    assert_eq!(
+        evaluate("8/1k6/P7/8/8/8/8/7K w - - 0 1"),
+        80 - 15 + free_path - 4 * 3 * 6
+    );
+    // And blocks the pawn.
+    assert_eq!(
+        evaluate("k7/8/P7/8/8/8/8/K7 w - - 0 1"),
+        80 - 15 - 4 * 3 * 5
+    );
+    // Same for black.
+    assert_eq!(
+        evaluate("k7/8/8/8/8/p7/8/7K b - - 0 1"),
+        -(80 - 15 + free_path + 4 * 3)
+    );
+}
+
+#[test]
+fn test_pawn_hash_table() {
+    let table = PawnHashTable::new(1000);
+    let entry = PawnEntry {
+        middlegame: -42,
+        endgame: 17,
+        passed_pawns: u64::nth(A6),
+    };
+    let key = 0x1234_5678_9abc_def0;
+    assert_eq!(table.probe(key), None);
+    table.store(key, &entry);
+    assert_eq!(table.probe(key), Some(entry));
+    // Same slot, different key.
+    assert_eq!(table.probe(key ^ (1 << 40)), None);
+    // 1000 entries round up to 1024.
+    let other_key = key + 1024;
+    table.store(other_key, &PawnEntry::default());
+    assert_eq!(table.probe(key), None);
+    assert_eq!(table.probe(other_key).unwrap().passed_pawns, EMPTY_BOARD);
+}
+
+#[test]
+fn test_cached_evaluations_match() {
+    let evaluator = PawnStructureEvaluator::new();
+    let position = Position::from_fen("4k3/8/8/4p3/2P5/3P4/8/4K3 w - - 0 1").unwrap();
+    let score = evaluator.evaluate(&position);
+    assert_eq!(evaluator.evaluate(&position), score);
+    // The king moved: the cached pawn structure is used with new king distances.
+    let king_moved = Position::from_fen("8/8/3k4/4p3/2P5/3P4/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(
+        evaluator.evaluate(&king_moved),
+        PawnStructureEvaluator::new().evaluate(&king_moved)
+    );
+    assert_ne!(evaluator.evaluate(&king_moved), score);
+}
+
+#[test]
+fn test_pawns_on_the_last_rank_are_skipped() {
+    // from_fen rejects such positions, but not the decoding of the web UI's boards.
+    let mut position = Position::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    position.white.pawns |= u64::nth(H8);
+    position.black.pawns |= u64::nth(A1);
+    assert_eq!(
+        PawnStructureEvaluator::pawn_structure(&position),
+        PawnEntry::default()
+    );
+    assert_eq!(PawnStructureEvaluator::new().evaluate(&position), 0);
+}
@@ -3,13 +3,17 @@ use crate::chess::position::Position;
 use crate::chess::PieceType;
 use crate::chess::PlayerColor;
 
+use super::game_phase::{game_phase, taper};
 use super::PositionEvaluator;
 use crate::chess::bitboard::{BitArraySize, PlayerBitboard, SpecialMoveType};
 use crate::strum::IntoEnumIterator;
 use std::collections::HashMap;
 
+// Bonus of each piece for its square, with middlegame and endgame tables blended by the game
+// phase.
 pub struct PieceCoordinateEvaluator {
     piece_values: HashMap<(PieceType, PlayerColor), HashMap<u8, i32>>,
+    endgame_piece_values: HashMap<(PieceType, PlayerColor), HashMap<u8, i32>>,
 }
 
 fn get_bishop_map() -> HashMap<u8, i32> {
@@ -39,6 +43,23 @@ fn get_bishop_map() -> HashMap<u8, i32> {
     result
 }
 
+// On an open board, bishops want the long diagonals and stay away from the edges.
+fn get_bishop_endgame_map() -> HashMap<u8, i32> {
+    let mut result = HashMap::new();
+    for i in 0..64 {
+        let rank = i / 8;
+        let file = i % 8;
+        let value = match (rank, file) {
+            (3..=4, 3..=4) => 20, // Centermost squares, on both long diagonals
+            _ if rank == file || rank + file == 7 => 10, // Long diagonals
+            (1..=6, 1..=6) => 5,  // Inner squares
+            _ => -10,             // Edges
+        };
+        result.insert(i as u8, value);
+    }
+    result
+}
+
 fn get_knight_map() -> HashMap<u8, i32> {
     let mut result = HashMap::new();
     for i in 0..64 {
@@ -97,6 +118,24 @@ fn get_knight_map() -> HashMap<u8, i32> {
     result
 }
 
+// Short-ranged knights have to be central to reach pawns on both wings.
+fn get_knight_endgame_map() -> HashMap<u8, i32> {
+    let mut result = HashMap::new();
+    for i in 0..64 {
+        let rank = i / 8;
+        let file = i % 8;
+        let value = match (rank, file) {
+            (3..=4, 3..=4) => 25,  // Centermost squares
+            (2..=5, 2..=5) => 15,  // Central squares
+            (1..=6, 1..=6) => 0,   // Slightly outward
+            (0 | 7, 0 | 7) => -30, // Corners
+            _ => -15,              // Edges
+        };
+        result.insert(i as u8, value);
+    }
+    result
+}
+
 fn get_pawn_map() -> HashMap<u8, i32> {
     let mut result = HashMap::new();
     for i in 0..64 {
@@ -122,6 +161,26 @@ fn get_pawn_map() -> HashMap<u8, i32> {
     result
 }
 
+// Without pieces to stop them, advanced pawns are worth much more, wherever their file.
+fn get_pawn_endgame_map() -> HashMap<u8, i32> {
+    let mut result = HashMap::new();
+    for i in 0..64 {
+        let rank = i / 8;
+        let value = match rank {
+            0 | 1 => 0, // Impossible, starting position
+            2 => 10,
+            3 => 20,
+            4 => 35,
+            5 => 60,
+            6 => 100,            // Close to promotion
+            7 => 0,              // Promoted (not relevant for PST)
+            _ => unreachable!(), // Should not happen
+        };
+        result.insert(i as u8, value);
+    }
+    result
+}
+
 fn get_queen_map() -> HashMap<u8, i32> {
     let mut result = HashMap::new();
     for i in 0..64 {
@@ -137,6 +196,23 @@ fn get_queen_map() -> HashMap<u8, i32> {
     result
 }
 
+// The queen centralizes, from where it reaches every part of the board.
+fn get_queen_endgame_map() -> HashMap<u8, i32> {
+    let mut result = HashMap::new();
+    for i in 0..64 {
+        let rank = i / 8;
+        let file = i % 8;
+        let value = match (rank, file) {
+            (3..=4, 3..=4) => 25, // Centermost squares
+            (2..=5, 2..=5) => 15, // Central squares
+            (1..=6, 1..=6) => 5,  // Slightly outward
+            _ => -10,             // Edges
+        };
+        result.insert(i as u8, value);
+    }
+    result
+}
+
 fn get_rook_map() -> HashMap<u8, i32> {
     let mut result = HashMap::new();
     for i in 0..64 {
@@ -152,6 +228,22 @@ fn get_rook_map() -> HashMap<u8, i32> {
     result
 }
 
+// On the seventh rank, the rook attacks the pawns left on their starting squares and cuts off
+// the enemy king. Files matter less once the board is open.
+fn get_rook_endgame_map() -> HashMap<u8, i32> {
+    let mut result = HashMap::new();
+    for i in 0..64 {
+        let rank = i / 8;
+        let value = match rank {
+            6 => 20,    // Seventh rank
+            4 | 5 => 5, // Active ranks
+            _ => 0,
+        };
+        result.insert(i as u8, value);
+    }
+    result
+}
+
 fn get_king_map() -> HashMap<u8, i32> {
     let mut result = HashMap::new();
     for i in 0..64 {
@@ -172,6 +264,23 @@ fn get_king_map() -> HashMap<u8, i32> {
     result
 }
 
+// With few pieces left to attack it, the king joins the fight from the center.
+fn get_king_endgame_map() -> HashMap<u8, i32> {
+    let mut result = HashMap::new();
+    for i in 0..64 {
+        let rank = i / 8;
+        let file = i % 8;
+        let value = match (rank, file) {
+            (3..=4, 3..=4) => 30, // Centermost squares
+            (2..=5, 2..=5) => 20, // Central squares
+            (1..=6, 1..=6) => 0,  // Slightly outward
+            _ => -20,             // Edges, where the king is easily cornered
+        };
+        result.insert(i as u8, value);
+    }
+    result
+}
+
 fn reverse_map(original_map: &HashMap<u8, i32>) -> HashMap<u8, i32> {
     let mut reversed_map = HashMap::new();
     for (key, value) in original_map {
@@ -184,6 +293,7 @@ fn reverse_map(original_map: &HashMap<u8, i32>) -> HashMap<u8, i32> {
 impl PieceCoordinateEvaluator {
     pub fn new() -> Self {
         let mut piece_values = HashMap::new();
+        let mut endgame_piece_values = HashMap::new();
 
         let mut func_by_type: HashMap<PieceType, fn() -> HashMap<u8, i32>> = HashMap::new();
         func_by_type.insert(PieceType::Pawn, get_pawn_map);
@@ -193,25 +303,38 @@ impl PieceCoordinateEvaluator {
         func_by_type.insert(PieceType::Queen, get_queen_map);
         func_by_type.insert(PieceType::King, get_king_map);
 
+        let mut endgame_func_by_type: HashMap<PieceType, fn() -> HashMap<u8, i32>> = HashMap::new();
+        endgame_func_by_type.insert(PieceType::Pawn, get_pawn_endgame_map);
+        endgame_func_by_type.insert(PieceType::Knight, get_knight_endgame_map);
+        endgame_func_by_type.insert(PieceType::Bishop, get_bishop_endgame_map);
+        endgame_func_by_type.insert(PieceType::Rook, get_rook_endgame_map);
+        endgame_func_by_type.insert(PieceType::Queen, get_queen_endgame_map);
+        endgame_func_by_type.insert(PieceType::King, get_king_endgame_map);
+
         for piece_type in PieceType::iter() {
             let map = func_by_type[&piece_type]();
             piece_values.insert((piece_type, PlayerColor::Black), reverse_map(&map));
             piece_values.insert((piece_type, PlayerColor::White), map);
+            let map = endgame_func_by_type[&piece_type]();
+            endgame_piece_values.insert((piece_type, PlayerColor::Black), reverse_map(&map));
+            endgame_piece_values.insert((piece_type, PlayerColor::White), map);
         }
 
         PieceCoordinateEvaluator {
             piece_values: piece_values,
+            endgame_piece_values,
         }
     }
 
     fn count_pieces_of_type(
         &self,
         mut piece_set: BitB64,
+        piece_values: &HashMap<(PieceType, PlayerColor), HashMap<u8, i32>>,
         piece_type: PieceType,
         player_color: PlayerColor,
     ) -> i32 {
         let mut result = 0;
-        let piece_map = &self.piece_values[&(piece_type, player_color)];
+        let piece_map = &piece_values[&(piece_type, player_color)];
         while piece_set != 0 {
             let piece_id = piece_set.trailing_zeros() as u8;
             piece_set ^= u64::nth(piece_id);
@@ -219,21 +342,26 @@ impl PieceCoordinateEvaluator {
         }
         result
     }
-}
 
-impl PositionEvaluator for PieceCoordinateEvaluator {
-    fn evaluate(&self, position: &Position) -> i32 {
+    // White's bonuses minus black's, from the tables of |piece_values|.
+    fn square_balance(
+        &self,
+        position: &Position,
+        piece_values: &HashMap<(PieceType, PlayerColor), HashMap<u8, i32>>,
+    ) -> i32 {
         let mut score = 0;
 
         let (white, black) = (position.white, position.black);
         for piece_type in PieceType::iter() {
             score += self.count_pieces_of_type(
                 *white.pieces(piece_type),
+                piece_values,
                 piece_type,
                 PlayerColor::White,
             );
             score -= self.count_pieces_of_type(
                 *black.pieces(piece_type),
+                piece_values,
                 piece_type,
                 PlayerColor::Black,
             );
@@ -241,3 +369,11 @@ impl PositionEvaluator for PieceCoordinateEvaluator {
         score
     }
 }
+
+impl PositionEvaluator for PieceCoordinateEvaluator {
+    fn evaluate(&self, position: &Position) -> i32 {
+        let middlegame = self.square_balance(position, &self.piece_values);
+        let endgame = self.square_balance(position, &self.endgame_piece_values);
+        taper(middlegame, endgame, game_phase(position))
+    }
+}
@@ -1,74 +1,626 @@
 use crate::chess::bitboard::BitB64;
+use crate::chess::history::PositionHistory;
 use crate::chess::position;
-use crate::chess::position::Position;
-use crate::chess::ChessPiece;
+use crate::chess::position::{Position, PositionScore, UndoStack};
 use crate::chess::PieceType;
-use crate::chess::PlayerColor;
 
-use crate::move_gen::MovesMap;
+use crate::move_gen::move_list::Move;
 
+use super::material_evaluator::MaterialEvaluator;
 use super::PositionEvaluator;
-use crate::chess::bitboard::{BitArraySize, BitboardMove, PlayerBitboard, SpecialMoveType};
+use crate::chess::bitboard::{BitArraySize, PlayerBitboard, SpecialMoveType};
+use crate::search::info::{
+    nodes_per_second, InfoCallback, InfoScore, LineInfo, SearchInfo, CURRMOVE_DELAY,
+};
+use crate::search::limits::{SearchLimits, MAX_SEARCH_DEPTH};
+use crate::search::move_ordering::{
+    is_losing_capture, is_tactical, HistoryTable, KillerMoves, MovePicker,
+};
+use crate::search::params::{SearchParams, MAX_CHECK_EXTENSION};
+use crate::search::score::{
+    is_mate_score, mate_in_moves, mated_in, relative_to, INFINITE_SCORE, MATE_SCORE,
+};
+use crate::search::transposition_table::{
+    score_from_tt, score_to_tt, Bound, TranspositionTable, DEFAULT_TT_SIZE_MB,
+};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, OnceLock};
+use std::thread;
 use std::time::{Duration, Instant};
 
+// Bound on the positional part of the score change of a capture, for delta pruning.
+const DELTA_PRUNING_MARGIN: i32 = 200;
+// How many nodes are searched between two checks of the clock and the stop flag.
+const NODES_BETWEEN_LIMIT_CHECKS: u64 = 1024;
+pub const MAX_SEARCH_THREADS: usize = 256;
+
+// State shared by all the nodes that one thread searches.
+struct SearchContext<'a> {
+    limits: &'a SearchLimits,
+    transposition_table: &'a TranspositionTable,
+    // The game so far, followed by the line being searched.
+    history: PositionHistory,
+    // Moves of the line being searched, to take them back.
+    undo_stack: UndoStack,
+    killer_moves: KillerMoves,
+    history_table: HistoryTable,
+    // Nodes of this thread, of which |reported_nodes| are counted in |all_nodes|, the nodes of
+    // every thread.
+    nodes: u64,
+    reported_nodes: u64,
+    all_nodes: &'a AtomicU64,
+    // Raised when the main thread is done, for the helper threads to stop too.
+    helpers_stop: Option<&'a AtomicBool>,
+    // MultiPV: root moves that already have a line in the running iteration, left out of the
+    // search of the next line.
+    excluded_root_moves: Vec<Move>,
+    // The first iteration always completes, so that there is a move to play.
+    can_abort: bool,
+    aborted: bool,
+    started: Instant,
+    // Deepest ply reached in the running iteration.
+    seldepth: u8,
+    // Only the main thread sends SearchInfo.
+    reports_info: bool,
+}
+
+impl<'a> SearchContext<'a> {
+    fn new(
+        limits: &'a SearchLimits,
+        transposition_table: &'a TranspositionTable,
+        history: PositionHistory,
+        all_nodes: &'a AtomicU64,
+    ) -> Self {
+        SearchContext {
+            limits,
+            transposition_table,
+            history,
+            undo_stack: UndoStack::new(),
+            killer_moves: KillerMoves::new(),
+            history_table: HistoryTable::new(),
+            nodes: 0,
+            reported_nodes: 0,
+            all_nodes,
+            helpers_stop: None,
+            excluded_root_moves: vec![],
+            can_abort: false,
+            aborted: false,
+            started: Instant::now(),
+            seldepth: 0,
+            reports_info: false,
+        }
+    }
+
+    // Counts a node and reports whether the search must be abandoned.
+    fn visit_node(&mut self, ply: u8) -> bool {
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(ply);
+        let checkpoint = self.nodes.is_multiple_of(NODES_BETWEEN_LIMIT_CHECKS);
+        if checkpoint {
+            self.report_nodes();
+        }
+        if !self.can_abort || self.aborted {
+            return self.aborted;
+        }
+        let all_nodes = self.all_nodes();
+        if self.limits.max_nodes.is_some_and(|max| all_nodes >= max) {
+            self.aborted = true;
+        } else if checkpoint {
+            self.aborted = self.limits.is_stopped()
+                || self.limits.is_past_hard_deadline()
+                || self
+                    .helpers_stop
+                    .is_some_and(|stop| stop.load(Ordering::Relaxed));
+        }
+        self.aborted
+    }
+
+    // Nodes searched so far by every thread, as far as this one knows.
+    fn all_nodes(&self) -> u64 {
+        self.all_nodes.load(Ordering::Relaxed) + self.nodes - self.reported_nodes
+    }
+
+    // Adds the nodes searched since the last call to the count of every thread.
+    fn report_nodes(&mut self) {
+        self.all_nodes
+            .fetch_add(self.nodes - self.reported_nodes, Ordering::Relaxed);
+        self.reported_nodes = self.nodes;
+    }
+}
+
 pub struct MinimaxSearchEvaluator {
     leaf_evaluator: Box<dyn PositionEvaluator>,
+    // Piece values for the quiescence search.
+    material: MaterialEvaluator,
     depth: u8,
+    params: SearchParams,
+    // Kept between searches, and shared with whoever else holds it. Unless one is given, it is
+    // only built by the first search, with |hash_size_mb| megabytes.
+    transposition_table: OnceLock<Arc<TranspositionTable>>,
+    hash_size_mb: usize,
+    // Lazy SMP: the extra threads search the same position, and only help the main one by
+    // filling the transposition table. With a single thread, searches are deterministic.
+    threads: usize,
+    // Number of best moves to search, each with its own principal variation (MultiPV).
+    lines: usize,
+    info_callback: Option<InfoCallback>,
 }
 
 impl MinimaxSearchEvaluator {
     pub fn new(leaf_evaluator: Box<dyn PositionEvaluator>, depth: u8) -> Self {
         Self {
             leaf_evaluator,
+            material: MaterialEvaluator::new(),
             depth,
+            params: SearchParams::default(),
+            transposition_table: OnceLock::new(),
+            hash_size_mb: DEFAULT_TT_SIZE_MB,
+            threads: 1,
+            lines: 1,
+            info_callback: None,
+        }
+    }
+
+    pub fn with_transposition_table(
+        mut self,
+        transposition_table: Arc<TranspositionTable>,
+    ) -> Self {
+        self.transposition_table = OnceLock::from(transposition_table);
+        self
+    }
+
+    // Size of the transposition table built by the first search, when none was given.
+    pub fn with_hash_size(mut self, size_mb: usize) -> Self {
+        self.hash_size_mb = size_mb;
+        self
+    }
+
+    fn transposition_table(&self) -> &TranspositionTable {
+        self.transposition_table
+            .get_or_init(|| Arc::new(TranspositionTable::new(self.hash_size_mb)))
+    }
+
+    // Check extensions are capped at MAX_CHECK_EXTENSION plies.
+    pub fn with_params(mut self, mut params: SearchParams) -> Self {
+        let check_extensions = &mut params.check_extensions;
+        check_extensions.plies = check_extensions.plies.min(MAX_CHECK_EXTENSION);
+        self.params = params;
+        self
+    }
+
+    // See search_lines(). At least one line is searched.
+    pub fn with_multi_pv(mut self, lines: usize) -> Self {
+        self.lines = lines.max(1);
+        self
+    }
+
+    // |threads| is clamped to 1..=MAX_SEARCH_THREADS.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.clamp(1, MAX_SEARCH_THREADS);
+        self
+    }
+
+    // Has |callback| called with the progress of every search. It is called on the search's
+    // main thread, which waits for it to return.
+    pub fn with_info_callback(
+        mut self,
+        callback: impl Fn(&SearchInfo) + Send + Sync + 'static,
+    ) -> Self {
+        self.info_callback = Some(Arc::new(callback));
+        self
+    }
+
+    // Like with_info_callback(), sending the progress to |sender|. Nothing is sent once the
+    // receiver is gone.
+    pub fn with_info_channel(self, sender: Sender<SearchInfo>) -> Self {
+        self.with_info_callback(move |info| {
+            let _ = sender.send(info.clone());
+        })
+    }
+
+    fn report(&self, info: SearchInfo) {
+        if let Some(callback) = &self.info_callback {
+            callback(&info);
         }
     }
 
-    fn minimax(
+    // Static evaluation from the point of view of the player to move.
+    fn evaluate_relative(&self, position: &Position) -> i32 {
+        relative_to(
+            position.player_to_move(),
+            self.leaf_evaluator.evaluate(position),
+        )
+    }
+
+    // Alpha-beta search in its negamax form: scores are from the point of view of the player
+    // to move, and the score of a move is the opposite of the score of the position it leads
+    // to. |null_move_allowed| is false right after a null move. When the context aborts, the
+    // returned result is meaningless and must be discarded.
+    #[allow(clippy::too_many_arguments)]
+    fn negamax(
         &self,
-        position: &Position,
+        ctx: &mut SearchContext,
+        position: &mut Position,
         remaining_depth: u8,
+        ply: u8,
         mut alpha: i32,
-        mut beta: i32,
-    ) -> (i32, i32) {
-        if remaining_depth == 0 {
-            return (self.leaf_evaluator.evaluate(position), 1);
-        }
-        let moving_player = position.player_to_move();
-        let mut best_score = match moving_player {
-            PlayerColor::White => i32::MIN,
-            PlayerColor::Black => i32::MAX,
+        beta: i32,
+        null_move_allowed: bool,
+    ) -> SearchResult {
+        let mut result = SearchResult {
+            score: 0,
+            nodes: 0,
+            elapsed: Duration::ZERO,
+            pv: vec![],
+            depth: remaining_depth,
         };
-        let mut total_nodes_explored = 0;
-        let continuation_map = position.legal_continuations();
-        for (_from_id, piece_and_moves) in continuation_map.iter() {
-            for mv in piece_and_moves.moves.iter() {
-                let new_pos = position.make_move(
-                    mv,
-                    ChessPiece {
-                        typpe: piece_and_moves.typpe,
-                        color: position.player_to_move(),
-                    },
+        // Inside the search, a position that repeats once is scored as a draw: if repeating it
+        // was best once, it is best again.
+        if ply > 0
+            && (ctx.history.repetitions(position) > 0 || position.has_insufficient_material())
+        {
+            return result;
+        }
+        // Extensions could otherwise go on forever.
+        if remaining_depth == 0 || ply >= MAX_SEARCH_DEPTH {
+            result.score = self.quiescence(ctx, position, ply, alpha, beta, true);
+            return result;
+        }
+        if ctx.visit_node(ply) {
+            return result;
+        }
+
+        let key = position.position_info.zobrist_hash;
+        let tt_entry = ctx.transposition_table.probe(key);
+        // The root always searches, so that it returns a full principal variation.
+        if let Some(entry) = tt_entry.filter(|entry| ply > 0 && entry.depth >= remaining_depth) {
+            let score = score_from_tt(entry.score, ply);
+            let cutoff = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => score >= beta,
+                Bound::Upper => score <= alpha,
+            };
+            if cutoff {
+                result.score = score;
+                result.pv = entry.best_move.into_iter().collect();
+                return result;
+            }
+        }
+
+        let in_check = position.in_check();
+        // Nodes searched with a null window only need to know whether they beat alpha, which
+        // leaves room for guesses.
+        let prunable = beta - alpha == 1 && !in_check && ply > 0;
+        let static_eval = match prunable {
+            true => self.evaluate_relative(position),
+            false => 0,
+        };
+        let params = &self.params;
+        let depth = remaining_depth as i32;
+        if prunable && !is_mate_score(beta) {
+            let reverse_futility = &params.reverse_futility;
+            if reverse_futility.enabled
+                && remaining_depth <= reverse_futility.max_depth
+                && static_eval - reverse_futility.margin_per_depth * depth >= beta
+            {
+                result.score = static_eval;
+                return result;
+            }
+
+            let razoring = &params.razoring;
+            if razoring.enabled
+                && remaining_depth <= razoring.max_depth
+                && static_eval + razoring.margin_per_depth * depth <= alpha
+            {
+                let score = self.quiescence(ctx, position, ply, alpha, beta, true);
+                if ctx.aborted || score <= alpha {
+                    result.score = score;
+                    return result;
+                }
+            }
+
+            let null_move = &params.null_move;
+            if null_move.enabled
+                && null_move_allowed
+                && remaining_depth >= null_move.min_depth
+                && static_eval >= beta
+                && position.has_non_pawn_material()
+            {
+                let reduction = null_move
+                    .reduction
+                    .saturating_add(remaining_depth / null_move.depth_divisor.max(1));
+                let mut after_pass = *position;
+                after_pass.make_null_move();
+                let child = self.negamax(
+                    ctx,
+                    &mut after_pass,
+                    remaining_depth.saturating_sub(reduction.saturating_add(1)),
+                    ply + 1,
+                    -beta,
+                    -beta + 1,
+                    false,
                 );
-                let (score, nodes_explored) =
-                    self.minimax(&new_pos, remaining_depth - 1, alpha, beta);
-                total_nodes_explored += nodes_explored;
-                match moving_player {
-                    PlayerColor::White => {
-                        best_score = best_score.max(score);
-                        alpha = alpha.max(best_score);
-                    }
-                    PlayerColor::Black => {
-                        best_score = best_score.min(score);
-                        beta = beta.min(best_score);
-                    }
+                if ctx.aborted {
+                    return result;
                 }
-                if alpha >= beta {
-                    break;
+                if -child.score >= beta {
+                    // Passing is not a real move, so a mate found after it proves nothing.
+                    result.score = match is_mate_score(child.score) {
+                        true => beta,
+                        false => -child.score,
+                    };
+                    return result;
+                }
+            }
+        }
+
+        let original_alpha = alpha;
+        let mut moves = position.legal_continuations();
+        if moves.is_empty() {
+            result.score = match in_check {
+                true => mated_in(ply),
+                // Stalemate.
+                false => 0,
+            };
+            return result;
+        }
+        if ply == 0 {
+            moves.retain(|mv| !ctx.excluded_root_moves.contains(mv));
+        }
+        if ply > 0 && position.is_fifty_move_draw() {
+            result.score = 0;
+            return result;
+        }
+        let tt_move = tt_entry.and_then(|entry| entry.best_move);
+        let move_picker = match params.move_ordering {
+            true => MovePicker::new(
+                moves,
+                position,
+                ply,
+                tt_move,
+                &ctx.killer_moves,
+                &ctx.history_table,
+            ),
+            false => MovePicker::unordered(moves),
+        };
+        let futile = prunable
+            && params.futility.enabled
+            && remaining_depth <= params.futility.max_depth
+            && static_eval + params.futility.margin_per_depth * depth <= alpha;
+        let lmr = &params.late_move_reductions;
+
+        result.score = -INFINITE_SCORE;
+        for (i, mv) in move_picker.enumerate() {
+            if ply == 0 && ctx.reports_info && ctx.started.elapsed() >= CURRMOVE_DELAY {
+                self.report(SearchInfo::CurrentMove {
+                    depth: remaining_depth,
+                    mv,
+                    number: i + 1,
+                });
+            }
+            let quiet = !is_tactical(position, &mv);
+            let killer = ctx.killer_moves.rank(ply, &mv).is_some();
+            position.make_move_in_place(mv, &mut ctx.undo_stack);
+            let gives_check = position.in_check();
+            if futile && i > 0 && quiet && !gives_check {
+                position.unmake_move(&mut ctx.undo_stack);
+                continue;
+            }
+            ctx.history.push(position);
+            let extension = match params.check_extensions.enabled && gives_check {
+                true => params.check_extensions.plies,
+                false => 0,
+            };
+            let child_depth = (remaining_depth - 1).saturating_add(extension);
+            let reduction = match lmr.enabled
+                && i >= lmr.min_move_index
+                && remaining_depth >= lmr.min_depth
+                && quiet
+                && !killer
+                && !in_check
+                && !gives_check
+            {
+                true => lmr.reduction.min(child_depth.saturating_sub(1)),
+                false => 0,
+            };
+            // With principal variation search, every move but the first only has to show that
+            // it beats alpha.
+            let child_beta = match i > 0 && params.principal_variation_search {
+                true => alpha + 1,
+                false => beta,
+            };
+            let mut child = self.negamax(
+                ctx,
+                position,
+                child_depth - reduction,
+                ply + 1,
+                -child_beta,
+                -alpha,
+                true,
+            );
+            // A reduced move that beats alpha is searched again at the full depth.
+            if reduction > 0 && -child.score > alpha && !ctx.aborted {
+                child = self.negamax(
+                    ctx,
+                    position,
+                    child_depth,
+                    ply + 1,
+                    -child_beta,
+                    -alpha,
+                    true,
+                );
+            }
+            // The move may be better than the best one so far: its exact score is needed.
+            if child_beta < beta && -child.score > alpha && -child.score < beta && !ctx.aborted {
+                child = self.negamax(ctx, position, child_depth, ply + 1, -beta, -alpha, true);
+            }
+            ctx.history.pop();
+            position.unmake_move(&mut ctx.undo_stack);
+            if ctx.aborted {
+                return result;
+            }
+            if -child.score > result.score {
+                result.score = -child.score;
+                result.pv = vec![mv];
+                result.pv.extend(child.pv);
+            }
+            alpha = alpha.max(result.score);
+            if alpha >= beta {
+                if quiet {
+                    ctx.killer_moves.store(ply, mv);
+                    ctx.history_table
+                        .reward(position.player_to_move(), &mv, remaining_depth);
                 }
+                break;
+            }
+        }
+
+        let bound = if result.score <= original_alpha {
+            Bound::Upper
+        } else if result.score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        // Without some of its moves, the root's score is not its own.
+        if ply > 0 || ctx.excluded_root_moves.is_empty() {
+            ctx.transposition_table.store(
+                key,
+                remaining_depth,
+                bound,
+                score_to_tt(result.score, ply),
+                result.best_move(),
+            );
+        }
+        result
+    }
+
+    // Searches captures and promotions until the position is quiet, so that the leaf evaluator
+    // is not called in the middle of an exchange. The side to move may also "stand pat", i.e.
+    // keep the static evaluation instead of capturing. In check, all evasions are searched.
+    // With |quiet_checks|, quiet moves that give check are searched too: the searches cut short
+    // by null moves and razoring would otherwise overlook the mates they threaten.
+    // Scores are from the point of view of the player to move.
+    fn quiescence(
+        &self,
+        ctx: &mut SearchContext,
+        position: &mut Position,
+        ply: u8,
+        mut alpha: i32,
+        beta: i32,
+        quiet_checks: bool,
+    ) -> i32 {
+        if ctx.visit_node(ply) {
+            return 0;
+        }
+        let stand_pat = self.evaluate_relative(position);
+        if ply >= MAX_SEARCH_DEPTH {
+            return stand_pat;
+        }
+        let in_check = position.in_check();
+        let mut moves = match in_check || quiet_checks {
+            true => position.legal_continuations(),
+            false => position.legal_captures(),
+        };
+        if in_check && moves.is_empty() {
+            return mated_in(ply);
+        }
+
+        let mut best = match in_check {
+            true => -INFINITE_SCORE,
+            false => stand_pat,
+        };
+        if !in_check {
+            if stand_pat >= beta {
+                return stand_pat;
             }
+            alpha = alpha.max(stand_pat);
+        }
+
+        // Most valuable victims first.
+        moves.sort_by_cached_key(|mv| -self.material_gain(position, mv));
+        for mv in moves.iter() {
+            // Delta pruning: skip the captures that cannot raise the score up to the window,
+            // even with a positional bonus on top of the material. Neither can captures that
+            // lose material in the exchange that follows. Quiet moves are only there for checks.
+            let pruned = !in_check
+                && (!is_tactical(position, mv)
+                    || stand_pat + self.material_gain(position, mv) + DELTA_PRUNING_MARGIN
+                        <= alpha
+                    || is_losing_capture(position, mv));
+            position.make_move_in_place(*mv, &mut ctx.undo_stack);
+            // Unless they give check, which may be mate.
+            if pruned && !position.in_check() {
+                position.unmake_move(&mut ctx.undo_stack);
+                continue;
+            }
+            let score = -self.quiescence(ctx, position, ply + 1, -beta, -alpha, false);
+            position.unmake_move(&mut ctx.undo_stack);
+            if ctx.aborted {
+                return 0;
+            }
+            best = best.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        best
+    }
+
+    // Material won by |mv|: the captured piece, plus the promoted piece in place of the pawn.
+    fn material_gain(&self, position: &Position, mv: &Move) -> i32 {
+        let captured = position.captured_piece_type(mv);
+        let promoted = mv.sp_move_type().promotion_piece_type();
+        captured.map_or(0, |typpe| self.material.piece_value(typpe))
+            + promoted.map_or(0, |typpe| {
+                self.material.piece_value(typpe) - self.material.piece_value(PieceType::Pawn)
+            })
+    }
+}
+
+pub struct SearchResult {
+    // From white's point of view.
+    pub score: i32,
+    // Nodes visited by the whole search, by all its threads, only filled in for the root.
+    pub nodes: u64,
+    // Duration of the whole search, only filled in for the root.
+    pub elapsed: Duration,
+    // Depth of the deepest completed iteration.
+    pub depth: u8,
+    // Principal variation: the best line found, starting with the move to play. Empty at the
+    // horizon and when there are no legal moves.
+    pub pv: Vec<Move>,
+}
+
+impl SearchResult {
+    pub fn best_move(&self) -> Option<Move> {
+        self.pv.first().copied()
+    }
+
+    // Nodes per second, over the whole search.
+    pub fn nps(&self) -> u64 {
+        nodes_per_second(self.nodes, self.elapsed)
+    }
+
+    // The score of |root|, the searched position.
+    pub fn position_score(&self, root: &Position) -> PositionScore {
+        PositionScore {
+            score: self.score,
+            mate_in: mate_in_moves(self.score).map_or(0, |moves| moves.unsigned_abs() as u8),
+            metadata: 0,
+            pinned_pieces: root.pinned_pieces(),
         }
-        (best_score, total_nodes_explored)
+    }
+
+    // The principal variation in coordinate notation, e.g. "e2e4 e7e5 g1f3".
+    pub fn pv_to_coordinates(&self) -> String {
+        self.pv
+            .iter()
+            .map(|mv| mv.to_coordinates())
+            .collect::<Vec<String>>()
+            .join(" ")
     }
 }
 
@@ -78,18 +630,203 @@ pub struct MoveScore {
     // pub confidence: u16,
 }
 
+impl MinimaxSearchEvaluator {
+    // Searches |position| to the configured depth.
+    pub fn search(&self, position: &Position) -> SearchResult {
+        self.search_with_limits(position, &SearchLimits::for_depth(self.depth))
+    }
+
+    // Iterative deepening: searches depth 1, 2, ... until |limits| say to stop, and returns
+    // the result of the last iteration that completed.
+    pub fn search_with_limits(&self, position: &Position, limits: &SearchLimits) -> SearchResult {
+        self.search_with_history(position, &PositionHistory::new(position), limits)
+    }
+
+    // Like search_with_limits(), for the last position of |history|. The search then avoids,
+    // or looks for, repetitions of the earlier positions of the game.
+    pub fn search_with_history(
+        &self,
+        position: &Position,
+        history: &PositionHistory,
+        limits: &SearchLimits,
+    ) -> SearchResult {
+        self.search_lines(position, history, limits).remove(0)
+    }
+
+    // Like search_with_history(), but returns the best lines, best first: as many as set by
+    // with_multi_pv(), or fewer when there are fewer legal moves. Each line is the best one
+    // that starts with a move other than the first moves of the lines before it. There is
+    // always at least one line, with an empty principal variation when there are no legal
+    // moves.
+    pub fn search_lines(
+        &self,
+        position: &Position,
+        history: &PositionHistory,
+        limits: &SearchLimits,
+    ) -> Vec<SearchResult> {
+        let start = Instant::now();
+        self.transposition_table().new_search();
+        let all_nodes = AtomicU64::new(0);
+        let helpers_stop = AtomicBool::new(false);
+        let mut lines = thread::scope(|scope| {
+            for thread_id in 1..self.threads {
+                let (all_nodes, helpers_stop) = (&all_nodes, &helpers_stop);
+                scope.spawn(move || {
+                    let mut ctx = SearchContext::new(
+                        limits,
+                        self.transposition_table(),
+                        history.clone(),
+                        all_nodes,
+                    );
+                    ctx.helpers_stop = Some(helpers_stop);
+                    ctx.can_abort = true;
+                    // Only the main thread's lines are returned.
+                    self.iterative_deepening(&mut ctx, position, thread_id, 1);
+                    ctx.report_nodes();
+                });
+            }
+            let mut ctx = SearchContext::new(
+                limits,
+                self.transposition_table(),
+                history.clone(),
+                &all_nodes,
+            );
+            ctx.started = start;
+            ctx.reports_info = true;
+            let lines = self.iterative_deepening(&mut ctx, position, 0, self.lines);
+            ctx.report_nodes();
+            helpers_stop.store(true, Ordering::Relaxed);
+            lines
+        });
+        // The helper threads have all returned.
+        for line in lines.iter_mut() {
+            line.nodes = all_nodes.load(Ordering::Relaxed);
+            line.elapsed = start.elapsed();
+        }
+        lines
+    }
+
+    // Iterative deepening, on thread |thread_id| (0 for the main thread), for |num_lines|
+    // lines. Every other helper thread searches one ply deeper than the main thread, so that
+    // the threads do not all search the same nodes at the same time.
+    fn iterative_deepening(
+        &self,
+        ctx: &mut SearchContext,
+        position: &Position,
+        thread_id: usize,
+        num_lines: usize,
+    ) -> Vec<SearchResult> {
+        let mut best = vec![SearchResult {
+            score: self.leaf_evaluator.evaluate(position),
+            nodes: 0,
+            elapsed: Duration::ZERO,
+            pv: vec![],
+            depth: 0,
+        }];
+        let mut root = *position;
+        let num_lines = num_lines.min(root.legal_continuations().len()).max(1);
+        // Scores of the lines of the last iteration, from the point of view of the player to
+        // move.
+        let mut previous_scores = vec![];
+        let max_depth = ctx.limits.max_depth.max(1);
+        for depth in 1..=max_depth {
+            let depth = (depth + (thread_id % 2) as u8).min(max_depth);
+            let mut lines: Vec<SearchResult> = vec![];
+            ctx.excluded_root_moves.clear();
+            ctx.seldepth = 0;
+            while lines.len() < num_lines && !ctx.aborted {
+                let previous_score = previous_scores.get(lines.len()).copied();
+                let line = self.aspiration_search(ctx, &mut root, depth, previous_score);
+                ctx.excluded_root_moves.extend(line.best_move());
+                lines.push(line);
+            }
+            if ctx.aborted {
+                break;
+            }
+            // A line found later can still score better, when the search is unstable.
+            lines.sort_by_key(|line| -line.score);
+            previous_scores = lines.iter().map(|line| line.score).collect();
+            for line in lines.iter_mut() {
+                line.score = relative_to(root.player_to_move(), line.score);
+            }
+            best = lines;
+            ctx.can_abort = true;
+            if ctx.reports_info {
+                self.report_lines(ctx, &best);
+            }
+            // Without legal moves, deeper searches find nothing new. Neither do they once mates
+            // within the full-width horizon have been proven.
+            let proven_mates = best.iter().all(|line| {
+                is_mate_score(line.score) && MATE_SCORE - line.score.abs() <= depth as i32
+            });
+            if best[0].pv.is_empty()
+                || proven_mates
+                || depth == max_depth
+                || ctx.limits.is_stopped()
+                || ctx.limits.is_past_soft_deadline()
+            {
+                break;
+            }
+        }
+        best
+    }
+
+    // Sends the lines of a completed iteration, best first.
+    fn report_lines(&self, ctx: &SearchContext, lines: &[SearchResult]) {
+        let (nodes, time) = (ctx.all_nodes(), ctx.started.elapsed());
+        let hashfull = ctx.transposition_table.hashfull();
+        for (i, line) in lines.iter().enumerate() {
+            self.report(SearchInfo::Line(LineInfo {
+                depth: line.depth,
+                seldepth: ctx.seldepth,
+                multipv: i + 1,
+                score: InfoScore::from_score(line.score),
+                pv: line.pv.clone(),
+                nodes,
+                nps: nodes_per_second(nodes, time),
+                time,
+                hashfull,
+            }));
+        }
+    }
+
+    // Searches |root| to |depth| with a window around |previous_score|, the score of the last
+    // iteration from the point of view of the player to move. Returns the same as negamax().
+    fn aspiration_search(
+        &self,
+        ctx: &mut SearchContext,
+        root: &mut Position,
+        depth: u8,
+        previous_score: Option<i32>,
+    ) -> SearchResult {
+        let params = &self.params.aspiration;
+        let mut delta = params.initial_delta;
+        let (mut alpha, mut beta) = match previous_score {
+            Some(score) if params.enabled && depth >= params.min_depth && !is_mate_score(score) => {
+                (score - delta, score + delta)
+            }
+            _ => (-INFINITE_SCORE, INFINITE_SCORE),
+        };
+        loop {
+            let result = self.negamax(ctx, root, depth, 0, alpha, beta, true);
+            if ctx.aborted {
+                return result;
+            }
+            delta *= 2;
+            if result.score <= alpha && alpha > -INFINITE_SCORE {
+                alpha = (alpha - delta).max(-INFINITE_SCORE);
+            } else if result.score >= beta && beta < INFINITE_SCORE {
+                beta = (beta + delta).min(INFINITE_SCORE);
+            } else {
+                return result;
+            }
+        }
+    }
+}
+
 impl PositionEvaluator for MinimaxSearchEvaluator {
+    // The search statistics go to the info subscribers, if any.
     fn evaluate(&self, position: &Position) -> i32 {
-        let start = Instant::now();
-        let (score, nodes_explored) = self.minimax(position, self.depth, i32::MIN, i32::MAX);
-        println!("Nodes explored: {}", nodes_explored);
-        println!("Score: {}", score);
-        let duration = start.elapsed();
-        println!("Duration: {:?}", duration);
-        println!(
-            "microsseconds per node: {}",
-            duration.as_micros() / nodes_explored as u128
-        );
-        score
+        self.search(position).score
     }
 }
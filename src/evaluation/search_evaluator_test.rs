@@ -0,0 +1,375 @@
+use super::search_evaluator::MinimaxSearchEvaluator;
+use super::PositionEvaluationPipeline;
+use crate::chess::history::PositionHistory;
+use crate::chess::position::Position;
+use crate::search::info::{InfoScore, LineInfo, SearchInfo};
+use crate::search::limits::{SearchLimits, MAX_SEARCH_DEPTH};
+use crate::search::params::SearchParams;
+use crate::search::score::MATE_SCORE;
+use crate::search::transposition_table::TranspositionTable;
+use std::sync::atomic::AtomicBool;
+use std::sync::{mpsc, Arc};
+
+const KIWIPETE_FEN: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn search(fen: &str, depth: u8) -> super::search_evaluator::SearchResult {
+    let evaluator =
+        MinimaxSearchEvaluator::new(Box::new(PositionEvaluationPipeline::new_default()), depth);
+    evaluator.search(&Position::from_fen(fen).unwrap())
+}
+
+#[test]
+fn test_best_move_captures_hanging_queen() {
+    let result = search("4k3/8/8/8/8/8/3q4/3Q3K w - - 0 1", 2);
+    assert_eq!(result.best_move().unwrap().to_coordinates(), "d1d2");
+    assert!(result.score > 0);
+
+    let result = search("3q3k/3Q4/8/8/8/8/8/4K3 b - - 0 1", 2);
+    assert_eq!(result.best_move().unwrap().to_coordinates(), "d8d7");
+    assert!(result.score < 0);
+}
+
+#[test]
+fn test_pv_spans_the_search_depth() {
+    let result = search(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        3,
+    );
+    assert_eq!(result.pv.len(), 3);
+    assert!(result.nodes > 0);
+
+    // Every move of the principal variation is legal in sequence.
+    let mut pos = Position::new();
+    for coordinates in result.pv_to_coordinates().split(' ') {
+        let mv = pos.find_legal_move(coordinates).unwrap();
+        pos = pos.play_move(mv);
+    }
+}
+
+#[test]
+fn test_search_with_limits_keeps_last_completed_iteration() {
+    let evaluator =
+        MinimaxSearchEvaluator::new(Box::new(PositionEvaluationPipeline::new_default()), 4);
+    let position = Position::from_fen("4k3/8/8/8/8/8/3q4/3Q3K w - - 0 1").unwrap();
+
+    // The node budget runs out long before depth 64, so the result comes from a shallower,
+    // fully searched iteration.
+    let limits = SearchLimits {
+        max_nodes: Some(2000),
+        ..SearchLimits::for_depth(MAX_SEARCH_DEPTH)
+    };
+    let result = evaluator.search_with_limits(&position, &limits);
+    assert!(result.depth >= 1 && result.depth < MAX_SEARCH_DEPTH);
+    assert_eq!(result.best_move().unwrap().to_coordinates(), "d1d2");
+
+    // A stop flag raised before the search starts still yields the depth 1 result.
+    let limits = SearchLimits {
+        stop: Some(Arc::new(AtomicBool::new(true))),
+        ..SearchLimits::for_depth(MAX_SEARCH_DEPTH)
+    };
+    let result = evaluator.search_with_limits(&position, &limits);
+    assert_eq!(result.depth, 1);
+    assert_eq!(result.best_move().unwrap().to_coordinates(), "d1d2");
+}
+
+#[test]
+fn test_transposition_table_is_reused_between_searches() {
+    let transposition_table = Arc::new(TranspositionTable::new(1));
+    let evaluator =
+        MinimaxSearchEvaluator::new(Box::new(PositionEvaluationPipeline::new_default()), 4)
+            .with_transposition_table(transposition_table.clone());
+    let position = Position::new();
+    let first = evaluator.search(&position);
+    let second = evaluator.search(&position);
+    assert_eq!(first.score, second.score);
+    assert_eq!(first.best_move(), second.best_move());
+    assert!(second.nodes < first.nodes);
+
+    let entry = transposition_table
+        .probe(position.position_info.zobrist_hash)
+        .unwrap();
+    assert_eq!(entry.depth, 4);
+    assert_eq!(entry.best_move, first.best_move());
+}
+
+#[test]
+fn test_quiescence_sees_the_recapture() {
+    // Qxd6 wins a pawn at depth 1, but exd6 takes the queen back.
+    let result = search("4k3/4p3/3p4/8/8/8/8/3QK3 w - - 0 1", 1);
+    assert_ne!(result.best_move().unwrap().to_coordinates(), "d1d6");
+    assert!(result.score > 0);
+
+    // Scores no longer swing between odd and even depths in the middle of an exchange.
+    let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/3PP3/5N2/PPP2PPP/RNBQKB1R b KQkq d3 0 3";
+    let (odd, even) = (search(fen, 1).score, search(fen, 2).score);
+    assert!((odd - even).abs() < 100, "{} vs {}", odd, even);
+}
+
+#[test]
+fn test_mate_scores() {
+    let mate_in = |fen: &str, depth: u8| -> (super::search_evaluator::SearchResult, u8) {
+        let result = search(fen, depth);
+        let mate_in = result
+            .position_score(&Position::from_fen(fen).unwrap())
+            .mate_in;
+        (result, mate_in)
+    };
+    let (result, moves) = mate_in("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 3);
+    assert_eq!(result.best_move().unwrap().to_coordinates(), "a1a8");
+    assert_eq!(result.score, MATE_SCORE - 1);
+    assert_eq!(moves, 1);
+
+    // Mate in 2, found at depth 4 rather than a longer mate.
+    let (result, moves) = mate_in("k7/8/2K5/8/8/8/8/7R w - - 0 1", 4);
+    assert_eq!(result.pv.len(), 3);
+    assert_eq!(result.score, MATE_SCORE - 3);
+    assert_eq!(moves, 2);
+
+    let (result, moves) = mate_in("K7/8/2k5/8/8/8/8/7r b - - 0 1", 4);
+    assert_eq!(result.score, -(MATE_SCORE - 3));
+    assert_eq!(moves, 2);
+
+    // Already checkmated.
+    let (result, moves) = mate_in("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1", 2);
+    assert!(result.pv.is_empty());
+    assert_eq!(result.score, MATE_SCORE);
+    assert_eq!(moves, 0);
+}
+
+#[test]
+fn test_stalemate_is_a_draw() {
+    let result = search("k7/8/1Q6/8/8/8/8/7K b - - 0 1", 2);
+    assert!(result.pv.is_empty());
+    assert_eq!(result.score, 0);
+
+    // Up a queen, white does not stalemate the king.
+    let position = Position::from_fen("k7/8/8/1Q6/8/8/8/7K w - - 0 1").unwrap();
+    let result = search("k7/8/8/1Q6/8/8/8/7K w - - 0 1", 2);
+    assert!(result.score > 0);
+    assert!(!position
+        .play_move(result.best_move().unwrap())
+        .legal_continuations()
+        .is_empty());
+}
+
+// |fen| followed by |moves|, as a UCI `position` command would give it.
+fn search_game(fen: &str, moves: &[&str], depth: u8) -> super::search_evaluator::SearchResult {
+    let mut position = Position::from_fen(fen).unwrap();
+    let mut history = PositionHistory::new(&position);
+    for coordinates in moves {
+        let mv = position.find_legal_move(coordinates).unwrap();
+        position = position.play_move(mv);
+        history.push(&position);
+    }
+    let evaluator =
+        MinimaxSearchEvaluator::new(Box::new(PositionEvaluationPipeline::new_default()), depth);
+    evaluator.search_with_history(&position, &history, &SearchLimits::for_depth(depth))
+}
+
+#[test]
+fn test_repetitions_are_draws() {
+    let shuffle = ["e1d1", "e8d8", "d1e1", "d8e8"];
+    // Down a queen, white repeats the position.
+    let result = search_game("q3k3/8/8/8/8/8/8/4K3 w - - 0 1", &shuffle, 2);
+    assert_eq!(result.best_move().unwrap().to_coordinates(), "e1d1");
+    assert_eq!(result.score, 0);
+
+    // Up a queen, white avoids repeating it.
+    let result = search_game("4k3/8/8/8/8/8/8/Q3K3 w - - 0 1", &shuffle, 2);
+    assert_ne!(result.best_move().unwrap().to_coordinates(), "e1d1");
+    assert!(result.score > 0);
+}
+
+#[test]
+fn test_fifty_move_rule_and_insufficient_material_are_draws() {
+    // Every white move is the 100th reversible one.
+    let result = search("q3k3/8/8/8/8/8/8/4K3 w - - 99 80", 2);
+    assert_eq!(result.score, 0);
+
+    let result = search("4k3/8/8/8/8/8/8/4KB2 w - - 0 1", 2);
+    assert_eq!(result.score, 0);
+}
+
+#[test]
+fn test_principal_variation_search_and_aspiration_windows_match_plain_alpha_beta() {
+    for fen in [
+        "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    ] {
+        let position = Position::from_fen(fen).unwrap();
+        // Depth 5: with the pawn structure terms the first position changes its best move in the
+        // last iteration at depth 4, and the root re-searches cost more than null windows save.
+        let [full_width, plain] = [SearchParams::full_width(), SearchParams::plain_alpha_beta()]
+            .map(|params| {
+                MinimaxSearchEvaluator::new(Box::new(PositionEvaluationPipeline::new_default()), 5)
+                    .with_params(params)
+                    .search(&position)
+            });
+        assert_eq!(full_width.score, plain.score, "{}", fen);
+        assert!(full_width.nodes <= plain.nodes, "{}", fen);
+    }
+}
+
+#[test]
+fn test_selective_search_finds_quiet_mates() {
+    // Mate in 2 with the quiet Qg6 and Qh7. Null moves and razoring end in the quiescence
+    // search right after Qg6, which has to see the threat.
+    let fen = "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1";
+    let result = search(fen, 4);
+    assert_eq!(result.best_move().unwrap().to_coordinates(), "g3g6");
+    assert_eq!(result.score, MATE_SCORE - 3);
+}
+
+#[test]
+fn test_selective_search_visits_fewer_nodes() {
+    let position =
+        Position::from_fen("r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP2BPPP/R2QKB1R w KQ - 0 8")
+            .unwrap();
+    let [selective, full_width] =
+        [SearchParams::default(), SearchParams::full_width()].map(|params| {
+            MinimaxSearchEvaluator::new(Box::new(PositionEvaluationPipeline::new_default()), 4)
+                .with_params(params)
+                .search(&position)
+        });
+    assert!(selective.nodes < full_width.nodes);
+}
+
+#[test]
+fn test_move_ordering_visits_fewer_nodes() {
+    let position =
+        Position::from_fen("r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP2BPPP/R2QKB1R w KQ - 0 8")
+            .unwrap();
+    let [ordered, unordered] = [true, false].map(|move_ordering| {
+        MinimaxSearchEvaluator::new(Box::new(PositionEvaluationPipeline::new_default()), 4)
+            .with_params(SearchParams {
+                move_ordering,
+                ..SearchParams::default()
+            })
+            .with_threads(1)
+            .search(&position)
+    });
+    // Searching the best moves first cuts off most of the tree.
+    assert!(3 * ordered.nodes < unordered.nodes);
+}
+
+#[test]
+fn test_extreme_params_do_not_panic() {
+    let mut params = SearchParams::default();
+    params.null_move.depth_divisor = 0;
+    params.late_move_reductions.min_depth = 0;
+    params.late_move_reductions.reduction = u8::MAX;
+    params.null_move.min_depth = 0;
+    params.null_move.reduction = u8::MAX;
+    params.check_extensions.plies = u8::MAX;
+    let result =
+        MinimaxSearchEvaluator::new(Box::new(PositionEvaluationPipeline::new_default()), 4)
+            .with_params(params)
+            .search(&Position::from_fen(KIWIPETE_FEN).unwrap());
+    assert!(result.best_move().is_some());
+}
+
+#[test]
+fn test_single_thread_searches_are_deterministic() {
+    let position = Position::from_fen(KIWIPETE_FEN).unwrap();
+    let [first, second] = [(), ()].map(|_| {
+        MinimaxSearchEvaluator::new(Box::new(PositionEvaluationPipeline::new_default()), 3)
+            .with_threads(1)
+            .search(&position)
+    });
+    assert_eq!(first.score, second.score);
+    assert_eq!(first.pv, second.pv);
+    assert_eq!(first.nodes, second.nodes);
+}
+
+#[test]
+fn test_threads_share_the_transposition_table() {
+    let position = Position::from_fen(KIWIPETE_FEN).unwrap();
+    let transposition_table = Arc::new(TranspositionTable::new(1));
+    let evaluator =
+        MinimaxSearchEvaluator::new(Box::new(PositionEvaluationPipeline::new_default()), 3)
+            .with_transposition_table(transposition_table.clone())
+            .with_threads(4);
+    let result = evaluator.search(&position);
+    assert!(position
+        .find_legal_move(&result.best_move().unwrap().to_coordinates())
+        .is_some());
+    assert_eq!(result.depth, 3);
+    assert!(result.nps() > 0);
+    let entry = transposition_table
+        .probe(position.position_info.zobrist_hash)
+        .unwrap();
+    assert!(entry.depth >= 3);
+}
+
+#[test]
+fn test_multi_pv_ranks_the_best_moves() {
+    let position = Position::from_fen("4k3/8/8/8/8/8/3q4/3Q3K w - - 0 1").unwrap();
+    let history = PositionHistory::new(&position);
+    let evaluator =
+        MinimaxSearchEvaluator::new(Box::new(PositionEvaluationPipeline::new_default()), 3);
+    let single = evaluator.search(&position);
+    let lines =
+        evaluator
+            .with_multi_pv(3)
+            .search_lines(&position, &history, &SearchLimits::for_depth(3));
+    assert_eq!(lines.len(), 3);
+    // The first line is the one a single line search finds.
+    assert_eq!(lines[0].best_move(), single.best_move());
+    assert_eq!(lines[0].score, single.score);
+    for pair in lines.windows(2) {
+        assert_ne!(pair[0].best_move(), pair[1].best_move());
+        assert!(pair[0].score >= pair[1].score);
+        assert_eq!(pair[1].depth, 3);
+    }
+    // Not taking the queen gives up winning it.
+    assert!(lines[0].score - lines[1].score > 500);
+
+    // No more lines than legal moves.
+    let position = Position::from_fen("1r5k/8/8/8/8/8/8/K7 w - - 0 1").unwrap();
+    let lines = MinimaxSearchEvaluator::new(Box::new(PositionEvaluationPipeline::new_default()), 2)
+        .with_multi_pv(5)
+        .search_lines(
+            &position,
+            &PositionHistory::new(&position),
+            &SearchLimits::for_depth(2),
+        );
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].best_move().unwrap().to_coordinates(), "a1a2");
+}
+
+#[test]
+fn test_search_reports_every_iteration() {
+    let position = Position::from_fen(KIWIPETE_FEN).unwrap();
+    let (sender, receiver) = mpsc::channel();
+    let result =
+        MinimaxSearchEvaluator::new(Box::new(PositionEvaluationPipeline::new_default()), 3)
+            .with_transposition_table(Arc::new(TranspositionTable::new(1)))
+            .with_multi_pv(2)
+            .with_info_channel(sender)
+            .search(&position);
+    let lines: Vec<LineInfo> = receiver
+        .try_iter()
+        .filter_map(|info| match info {
+            SearchInfo::Line(line) => Some(line),
+            SearchInfo::CurrentMove { .. } => None,
+        })
+        .collect();
+    let ranks: Vec<(u8, usize)> = lines
+        .iter()
+        .map(|line| (line.depth, line.multipv))
+        .collect();
+    assert_eq!(ranks, vec![(1, 1), (1, 2), (2, 1), (2, 2), (3, 1), (3, 2)]);
+    for pair in lines.windows(2) {
+        assert!(pair[0].nodes <= pair[1].nodes);
+        assert!(pair[0].hashfull <= pair[1].hashfull);
+    }
+    for line in lines.iter() {
+        // The quiescence search goes past the nominal depth.
+        assert!(line.seldepth > line.depth);
+    }
+    let last = lines.len() - 2;
+    assert!(lines[last].hashfull > 0);
+    assert_eq!(lines[last].pv, result.pv);
+    assert_eq!(lines[last].score, InfoScore::from_score(result.score));
+}
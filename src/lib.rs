@@ -0,0 +1,71 @@
+extern crate once_cell;
+pub mod chess;
+pub mod evaluation;
+pub mod move_gen;
+pub mod search;
+pub mod server;
+pub mod uci;
+
+// Strum contains all the trait definitions
+extern crate strum;
+#[macro_use]
+extern crate strum_macros;
+#[macro_use]
+extern crate rocket;
+extern crate serde;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+// IfChange:
+pub struct UciRequest {
+    pub p_to_move: String,
+    pub board: String,
+    pub req_type: String,
+    pub timeout: u32,
+    // Number of lines of a "multi_pv" request or of an analysis session, 3 when left out or 0.
+    // Other requests leave it out.
+    #[serde(default)]
+    pub multi_pv: u32,
+}
+
+#[derive(Responder, Serialize, Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+#[response(status = 200, content_type = "json")]
+
+pub struct UciResponse {
+    pub best_moves: String,
+    #[response(ignore)]
+    pub possible_moves: String,
+    #[response(ignore)]
+    pub pos_score: String,
+    // The lines of a "multi_pv" request, best first. Empty for other requests.
+    #[response(ignore)]
+    pub lines: Vec<AnalysisLine>,
+}
+
+// One line of a "multi_pv" request: a move, the score of the position after it in the format
+// of UciResponse.pos_score, and the principal variation it starts, searched |depth| plies
+// deep.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct AnalysisLine {
+    #[serde(rename = "move")]
+    pub mv: String,
+    pub score: String,
+    pub pv: String,
+    pub depth: u8,
+}
+
+// State of a background analysis started by POST /analysis/start. |lines| are those of the
+// deepest search completed so far, in the format of UciResponse.lines.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct AnalysisStatus {
+    pub session_id: u64,
+    pub running: bool,
+    pub lines: Vec<AnalysisLine>,
+}
+// ThenChange:
+// JS UciRequest.
@@ -1,55 +1,25 @@
-extern crate once_cell;
-pub mod chess;
-pub mod evaluation;
-pub mod move_gen;
-pub mod server;
+extern crate chess_gpt;
 
 // static RUNTIME: Lazy<EngineRuntime> = Lazy::new(|| { runtime::EngineRuntime::new()});
 
 // static RUNTIME: &EngineRuntime = &EngineRuntime::new();
 
-// Strum contains all the trait definitions
-extern crate strum;
-#[macro_use]
-extern crate strum_macros;
 #[macro_use]
 extern crate rocket;
-extern crate serde;
 
 use rocket::{
     fs::FileServer,
-    get, post,
+    get,
+    http::Status,
+    post,
     response::{self, Redirect, Responder},
     routes,
     serde::json::Json,
-    Build, Rocket,
+    Build, Rocket, State,
 };
 
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(crate = "rocket::serde")]
-// IfChange:
-pub struct UciRequest {
-    pub p_to_move: String,
-    pub board: String,
-    pub req_type: String,
-    pub timeout: u32,
-}
-
-#[derive(Responder, Serialize, Deserialize, Debug)]
-#[serde(crate = "rocket::serde")]
-#[response(status = 200, content_type = "json")]
-
-pub struct UciResponse {
-    pub best_moves: String,
-    #[response(ignore)]
-    pub possible_moves: String,
-    #[response(ignore)]
-    pub pos_score: String,
-}
-// ThenChange:
-// JS UciRequest.
+use chess_gpt::server::analysis::AnalysisSessions;
+use chess_gpt::{server, AnalysisStatus, UciRequest, UciResponse};
 
 #[get("/")]
 fn index() -> Redirect {
@@ -64,6 +34,12 @@ fn engine(wrapped_uci_req: Json<UciRequest>) -> Json<UciResponse> {
         server::possible_moves::handle_possible_moves_request(&uci_req)
     } else if req_type == "pos_eval" {
         server::position_eval::handle_position_eval_request(&uci_req)
+    } else if req_type == "best_move" {
+        server::best_move::handle_best_move_request(&uci_req)
+    } else if req_type == "move_safety" {
+        server::move_safety::handle_move_safety_request(&uci_req)
+    } else if req_type == "multi_pv" {
+        server::multi_pv::handle_multi_pv_request(&uci_req)
     } else {
         todo!()
     };
@@ -71,9 +47,44 @@ fn engine(wrapped_uci_req: Json<UciRequest>) -> Json<UciResponse> {
     Json(resp)
 }
 
+// Background analysis, for searches that run until the user stops them: start returns at once,
+// and the lines found so far are polled.
+#[post("/analysis/start", format = "json", data = "<wrapped_uci_req>")]
+fn start_analysis(
+    wrapped_uci_req: Json<UciRequest>,
+    sessions: &State<AnalysisSessions>,
+) -> Json<AnalysisStatus> {
+    Json(sessions.start(&wrapped_uci_req.into_inner()))
+}
+
+#[get("/analysis/<session_id>")]
+fn poll_analysis(
+    session_id: u64,
+    sessions: &State<AnalysisSessions>,
+) -> Option<Json<AnalysisStatus>> {
+    sessions.poll(session_id).map(Json)
+}
+
+#[post("/analysis/<session_id>/stop")]
+fn stop_analysis(
+    session_id: u64,
+    sessions: &State<AnalysisSessions>,
+) -> Option<Result<Json<AnalysisStatus>, (Status, String)>> {
+    let status = sessions.stop(session_id)?;
+    Some(
+        status
+            .map(Json)
+            .map_err(|error| (Status::InternalServerError, error)),
+    )
+}
+
 #[launch]
 fn rocket() -> Rocket<Build> {
     rocket::build()
-        .mount("/", routes![index, engine])
+        .manage(AnalysisSessions::default())
+        .mount(
+            "/",
+            routes![index, engine, start_analysis, poll_analysis, stop_analysis],
+        )
         .mount("/public", FileServer::from("public"))
 }
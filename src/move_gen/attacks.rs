@@ -0,0 +1,418 @@
+// Precomputed attack tables. Knights, kings and pawns attack a fixed set of squares from
+// each square. Rooks and bishops use magic bitboards: the blockers on the squares a slider
+// could reach are multiplied by a magic number, whose top bits index a table holding the
+// attacks for that exact set of blockers.
+use crate::chess::bitboard::{BitArraySize, BitB64, PlayerBitboard, EMPTY_BOARD, FULL_BOARD};
+use crate::chess::{ChessPiece, PieceType, PlayerColor};
+use crate::move_gen::internal::{get_ij_from_sq_id, get_sq_id_from_ij, is_inside_board};
+
+use once_cell::sync::Lazy;
+use strum::IntoEnumIterator;
+
+pub const ROOK_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+pub const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+const KNIGHT_OFFSETS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const KING_OFFSETS: [(i8, i8); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+// Found by trying sparse random numbers until one indexed every set of blockers of the square
+// without a harmful collision. Building the tables checks them again.
+const ROOK_MAGICS: [u64; 64] = [
+    0x008000908064c000,
+    0x0040200040001000,
+    0x0180100080a0010a,
+    0x8880041000800800,
+    0x1200100201200804,
+    0x0200020004011008,
+    0x2180010000800600,
+    0x0200005088210204,
+    0x0400800040008021,
+    0x0400400020005000,
+    0x8240801000200080,
+    0x8611001004200900,
+    0x008180800c001800,
+    0x0100800200800400,
+    0x0a02000102000408,
+    0x8020802300104280,
+    0x0080004000402000,
+    0xe010104000402000,
+    0x0800808010002000,
+    0xa280210008100100,
+    0x0001818014000800,
+    0xa002010100080400,
+    0x0080240001020870,
+    0x0001020004048845,
+    0x0081826280004004,
+    0x2020810900284000,
+    0x0200100080802000,
+    0x0200080080100080,
+    0x8083080100100500,
+    0x4406000901000400,
+    0x0005020080800100,
+    0x0090204200008114,
+    0x0010400094800420,
+    0x0900804000802002,
+    0x0201001841002000,
+    0x4100080080801000,
+    0x4540040080800800,
+    0x0002001004040020,
+    0x0281195814001002,
+    0x1240800040800100,
+    0x0880042000524004,
+    0x02c080410206002c,
+    0x0801200241050010,
+    0x8400080010008080,
+    0x0008000500090010,
+    0x0082009084020008,
+    0x4012000108020004,
+    0x9000104d08860004,
+    0x2004204114800100,
+    0x0148802112400300,
+    0x0202842000100880,
+    0x001b080080900080,
+    0x001a002008100600,
+    0x0004008004020080,
+    0x5181000600040300,
+    0x0000044401128a00,
+    0x8044110480002441,
+    0x2008110084402202,
+    0x90806005090010c1,
+    0x000420310a004a42,
+    0x0023001004020801,
+    0x0882001008040102,
+    0x000230088118020c,
+    0x0000019025040042,
+];
+const BISHOP_MAGICS: [u64; 64] = [
+    0x0045010808008680,
+    0x2002080204004898,
+    0x0210009a10400006,
+    0x0824050200810200,
+    0x0006061105004090,
+    0x00010108c0000000,
+    0x0814040282104004,
+    0x0012012201106800,
+    0x10823014100c1040,
+    0x0080c2088802808c,
+    0x0281108410404000,
+    0x0101212041826200,
+    0x0020141028221058,
+    0x2201020202200202,
+    0x000082a801482000,
+    0x0000008401411044,
+    0x0007103014300404,
+    0x0002091110010100,
+    0x42140012040c0808,
+    0x0800808802004020,
+    0x90c4004210140000,
+    0x0800200900a01000,
+    0x00d0400201108810,
+    0x80820183814412a0,
+    0x00a01008202202b4,
+    0x01c2021a09500402,
+    0x0084440208042400,
+    0x800400400c090100,
+    0xba10040010802100,
+    0xd182009006005000,
+    0x5011021001009004,
+    0x0020420200510400,
+    0x0292104000468800,
+    0x00043009091c0500,
+    0x0280441000020025,
+    0x0042820080080080,
+    0x0440101010010040,
+    0x1000900100808080,
+    0x0108108120089800,
+    0x0044010200012682,
+    0xc002500420900400,
+    0x0040482210710800,
+    0x0002060024000200,
+    0x0281020a44000800,
+    0xa0021200a4000200,
+    0x0001301000840840,
+    0x2868500108444220,
+    0x0004111041000200,
+    0x8044020842080200,
+    0x0000220104210200,
+    0x0000021201044000,
+    0x0000280884040028,
+    0x4012114010858003,
+    0x0000081004082b88,
+    0x3892700508208002,
+    0x00220a041b060400,
+    0x0812020284014881,
+    0x010434a282103100,
+    0x0490400824020800,
+    0x4a20002c00208800,
+    0x000000a011020200,
+    0x4002940a02482202,
+    0x5100100202140406,
+    0x02102000840540c1,
+];
+
+static ATTACK_TABLES: Lazy<AttackTables> = Lazy::new(AttackTables::new);
+
+// Squares attacked by |piece| standing on |sq_id|, given the pieces of both players in
+// |occupancy|. Sliders stop at, and include, the first occupied square in each direction.
+// Whether the attacked squares are occupied by allies is left to the caller.
+pub fn attacks(piece: ChessPiece, sq_id: u8, occupancy: BitB64) -> BitB64 {
+    match piece.typpe {
+        PieceType::Pawn => pawn_attacks(piece.color, sq_id),
+        PieceType::Knight => knight_attacks(sq_id),
+        PieceType::Bishop => bishop_attacks(sq_id, occupancy),
+        PieceType::Rook => rook_attacks(sq_id, occupancy),
+        PieceType::Queen => bishop_attacks(sq_id, occupancy) | rook_attacks(sq_id, occupancy),
+        PieceType::King => king_attacks(sq_id),
+    }
+}
+
+pub fn pawn_attacks(color: PlayerColor, sq_id: u8) -> BitB64 {
+    ATTACK_TABLES.pawn[color as usize][sq_id as usize]
+}
+
+pub fn knight_attacks(sq_id: u8) -> BitB64 {
+    ATTACK_TABLES.knight[sq_id as usize]
+}
+
+pub fn king_attacks(sq_id: u8) -> BitB64 {
+    ATTACK_TABLES.king[sq_id as usize]
+}
+
+pub fn bishop_attacks(sq_id: u8, occupancy: BitB64) -> BitB64 {
+    let tables = &*ATTACK_TABLES;
+    tables.bishop[sq_id as usize].attacks(&tables.sliding, occupancy)
+}
+
+pub fn rook_attacks(sq_id: u8, occupancy: BitB64) -> BitB64 {
+    let tables = &*ATTACK_TABLES;
+    tables.rook[sq_id as usize].attacks(&tables.sliding, occupancy)
+}
+
+// Every square attacked by |pieces|, which belong to |color|, whether or not it is occupied.
+pub fn attacked_squares(pieces: &PlayerBitboard, color: PlayerColor, occupancy: BitB64) -> BitB64 {
+    let mut result = EMPTY_BOARD;
+    for typpe in PieceType::iter() {
+        let piece = ChessPiece { typpe, color };
+        let mut piece_set = *pieces.pieces(typpe);
+        while piece_set != EMPTY_BOARD {
+            let sq_id = piece_set.trailing_zeros() as u8;
+            piece_set ^= u64::nth(sq_id);
+            result |= attacks(piece, sq_id, occupancy);
+        }
+    }
+    result
+}
+
+// Pieces of |attackers|, which belong to |color|, that attack |sq_id|.
+pub fn attackers_to(
+    sq_id: u8,
+    attackers: &PlayerBitboard,
+    color: PlayerColor,
+    occupancy: BitB64,
+) -> BitB64 {
+    // A pawn attacks the squares from which a pawn of the other color would attack it.
+    (pawn_attacks(PlayerColor::other(color), sq_id) & attackers.pawns)
+        | (knight_attacks(sq_id) & attackers.knights)
+        | (king_attacks(sq_id) & attackers.king)
+        | (bishop_attacks(sq_id, occupancy) & (attackers.bishops | attackers.queens))
+        | (rook_attacks(sq_id, occupancy) & (attackers.rooks | attackers.queens))
+}
+
+// Squares strictly between |a| and |b| if they share a rank, file or diagonal, none otherwise.
+pub fn between(a: u8, b: u8) -> BitB64 {
+    let (a_bit, b_bit) = (u64::nth(a), u64::nth(b));
+    if rook_attacks(a, EMPTY_BOARD) & b_bit != EMPTY_BOARD {
+        rook_attacks(a, b_bit) & rook_attacks(b, a_bit)
+    } else if bishop_attacks(a, EMPTY_BOARD) & b_bit != EMPTY_BOARD {
+        bishop_attacks(a, b_bit) & bishop_attacks(b, a_bit)
+    } else {
+        EMPTY_BOARD
+    }
+}
+
+// The whole rank, file or diagonal going through |a| and |b|, none if they are not aligned.
+pub fn line(a: u8, b: u8) -> BitB64 {
+    let ends = u64::nth(a) | u64::nth(b);
+    if rook_attacks(a, EMPTY_BOARD) & u64::nth(b) != EMPTY_BOARD {
+        (rook_attacks(a, EMPTY_BOARD) & rook_attacks(b, EMPTY_BOARD)) | ends
+    } else if bishop_attacks(a, EMPTY_BOARD) & u64::nth(b) != EMPTY_BOARD {
+        (bishop_attacks(a, EMPTY_BOARD) & bishop_attacks(b, EMPTY_BOARD)) | ends
+    } else {
+        EMPTY_BOARD
+    }
+}
+
+// Forces the tables to be built now rather than on first use, e.g. before the clock starts.
+pub fn init() {
+    Lazy::force(&ATTACK_TABLES);
+}
+
+struct Magic {
+    // Squares whose occupancy matters: the rays from the square, minus their last square.
+    mask: BitB64,
+    magic: u64,
+    shift: u32,
+    // Start of the attacks of this square in the shared table.
+    offset: usize,
+}
+
+impl Magic {
+    fn index(&self, occupancy: BitB64) -> usize {
+        ((occupancy & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+
+    fn attacks(&self, sliding: &[BitB64], occupancy: BitB64) -> BitB64 {
+        sliding[self.offset + self.index(occupancy)]
+    }
+}
+
+struct AttackTables {
+    pawn: [[BitB64; 64]; 2],
+    knight: [BitB64; 64],
+    king: [BitB64; 64],
+    rook: Vec<Magic>,
+    bishop: Vec<Magic>,
+    // Rook and bishop attacks for every square and set of blockers.
+    sliding: Vec<BitB64>,
+}
+
+impl AttackTables {
+    fn new() -> Self {
+        let mut pawn = [[EMPTY_BOARD; 64]; 2];
+        let mut knight = [EMPTY_BOARD; 64];
+        let mut king = [EMPTY_BOARD; 64];
+        for sq_id in 0..64u8 {
+            let sq = sq_id as usize;
+            pawn[PlayerColor::White as usize][sq] = offset_squares(sq_id, &[(1, -1), (1, 1)]);
+            pawn[PlayerColor::Black as usize][sq] = offset_squares(sq_id, &[(-1, -1), (-1, 1)]);
+            knight[sq] = offset_squares(sq_id, &KNIGHT_OFFSETS);
+            king[sq] = offset_squares(sq_id, &KING_OFFSETS);
+        }
+
+        let mut sliding = Vec::new();
+        let rook = (0..64)
+            .map(|sq_id| {
+                build_magic(
+                    sq_id,
+                    &ROOK_DIRECTIONS,
+                    ROOK_MAGICS[sq_id as usize],
+                    &mut sliding,
+                )
+            })
+            .collect();
+        let bishop = (0..64)
+            .map(|sq_id| {
+                build_magic(
+                    sq_id,
+                    &BISHOP_DIRECTIONS,
+                    BISHOP_MAGICS[sq_id as usize],
+                    &mut sliding,
+                )
+            })
+            .collect();
+        AttackTables {
+            pawn,
+            knight,
+            king,
+            rook,
+            bishop,
+            sliding,
+        }
+    }
+}
+
+fn offset_squares(sq_id: u8, offsets: &[(i8, i8)]) -> BitB64 {
+    let (i0, j0) = get_ij_from_sq_id(sq_id as i8);
+    let mut result = EMPTY_BOARD;
+    for (di, dj) in offsets {
+        let (i, j) = (i0 + di, j0 + dj);
+        if is_inside_board(i, j) {
+            result |= u64::nth(get_sq_id_from_ij(i, j) as u8);
+        }
+    }
+    result
+}
+
+// Slow reference implementation, stepping along each ray until it leaves the board or hits
+// a piece. Only used to fill the tables.
+pub fn sliding_attacks(sq_id: u8, directions: &[(i8, i8)], occupancy: BitB64) -> BitB64 {
+    let (i0, j0) = get_ij_from_sq_id(sq_id as i8);
+    let mut result = EMPTY_BOARD;
+    for (di, dj) in directions {
+        let (mut i, mut j) = (i0 + di, j0 + dj);
+        while is_inside_board(i, j) {
+            let sq = u64::nth(get_sq_id_from_ij(i, j) as u8);
+            result |= sq;
+            if occupancy & sq != EMPTY_BOARD {
+                break;
+            }
+            i += di;
+            j += dj;
+        }
+    }
+    result
+}
+
+// A piece on the last square of a ray blocks nothing, so those squares are left out.
+fn relevant_occupancy_mask(sq_id: u8, directions: &[(i8, i8)]) -> BitB64 {
+    let (i0, j0) = get_ij_from_sq_id(sq_id as i8);
+    let mut result = EMPTY_BOARD;
+    for (di, dj) in directions {
+        let (mut i, mut j) = (i0 + di, j0 + dj);
+        while is_inside_board(i + di, j + dj) {
+            result |= u64::nth(get_sq_id_from_ij(i, j) as u8);
+            i += di;
+            j += dj;
+        }
+    }
+    result
+}
+
+// Appends the attacks of a slider on |sq_id| for every set of blockers to |sliding|, at the
+// slots |magic| maps them to.
+fn build_magic(sq_id: u8, directions: &[(i8, i8)], magic: u64, sliding: &mut Vec<BitB64>) -> Magic {
+    let mask = relevant_occupancy_mask(sq_id, directions);
+    let bits = mask.count_ones();
+    let result = Magic {
+        mask,
+        magic,
+        shift: 64 - bits,
+        offset: sliding.len(),
+    };
+    sliding.resize(result.offset + (1 << bits), FULL_BOARD);
+
+    // Every subset of the mask, enumerated with the Carry-Rippler trick.
+    let mut occupancy = EMPTY_BOARD;
+    loop {
+        let attacks = sliding_attacks(sq_id, directions, occupancy);
+        let slot = &mut sliding[result.offset + result.index(occupancy)];
+        // Two sets of blockers may share a slot only if they leave the same squares attacked.
+        assert!(
+            *slot == FULL_BOARD || *slot == attacks,
+            "bad magic for square {}",
+            sq_id
+        );
+        *slot = attacks;
+        occupancy = occupancy.wrapping_sub(mask) & mask;
+        if occupancy == EMPTY_BOARD {
+            break;
+        }
+    }
+    result
+}
@@ -0,0 +1,77 @@
+use super::attacks::{attacks, sliding_attacks, BISHOP_DIRECTIONS, ROOK_DIRECTIONS};
+use crate::chess::bitboard::{
+    BitArraySize, BitB64, A1, A2, A8, B1, B3, B7, C2, D4, D5, E4, E5, F4, F5, G3, H2, H8,
+};
+use crate::chess::{ChessPiece, PieceType, PlayerColor};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+fn piece(typpe: PieceType, color: PlayerColor) -> ChessPiece {
+    ChessPiece { typpe, color }
+}
+
+fn squares(sq_ids: &[u8]) -> BitB64 {
+    sq_ids
+        .iter()
+        .fold(0, |result, sq_id| result | u64::nth(*sq_id))
+}
+
+#[test]
+fn test_leaper_attacks() {
+    let knight = piece(PieceType::Knight, PlayerColor::White);
+    assert_eq!(attacks(knight, A1, 0), squares(&[B3, C2]));
+    assert_eq!(attacks(knight, E4, 0).count_ones(), 8);
+    assert_eq!(attacks(knight, H8, 0).count_ones(), 2);
+
+    let king = piece(PieceType::King, PlayerColor::Black);
+    assert_eq!(attacks(king, A1, 0).count_ones(), 3);
+    assert_eq!(attacks(king, E4, u64::MAX).count_ones(), 8);
+
+    // Pawns attack diagonally forward, and only there.
+    let white_pawn = piece(PieceType::Pawn, PlayerColor::White);
+    let black_pawn = piece(PieceType::Pawn, PlayerColor::Black);
+    assert_eq!(attacks(white_pawn, E4, 0), squares(&[D5, F5]));
+    assert_eq!(attacks(black_pawn, E5, 0), squares(&[D4, F4]));
+    assert_eq!(attacks(white_pawn, H2, 0), squares(&[G3]));
+    assert_eq!(attacks(white_pawn, A8, 0), 0);
+}
+
+#[test]
+fn test_slider_attacks() {
+    let rook = piece(PieceType::Rook, PlayerColor::White);
+    assert_eq!(attacks(rook, A1, 0).count_ones(), 14);
+    // Blockers are attacked, squares behind them are not.
+    assert_eq!(attacks(rook, A1, squares(&[A2, B1])), squares(&[A2, B1]));
+
+    let bishop = piece(PieceType::Bishop, PlayerColor::Black);
+    assert_eq!(attacks(bishop, A1, 0).count_ones(), 7);
+    assert_eq!(attacks(bishop, E4, squares(&[C2, B7])).count_ones(), 11);
+
+    let queen = piece(PieceType::Queen, PlayerColor::White);
+    assert_eq!(attacks(queen, D4, 0).count_ones(), 27);
+    assert_eq!(
+        attacks(queen, E5, squares(&[E4])),
+        attacks(rook, E5, squares(&[E4])) | attacks(bishop, E5, squares(&[E4]))
+    );
+}
+
+#[test]
+fn test_slider_attacks_match_ray_stepping() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let rook = piece(PieceType::Rook, PlayerColor::White);
+    let bishop = piece(PieceType::Bishop, PlayerColor::White);
+    for sq_id in 0..64u8 {
+        for _ in 0..100 {
+            let occupancy = rng.gen::<u64>() & rng.gen::<u64>();
+            assert_eq!(
+                attacks(rook, sq_id, occupancy),
+                sliding_attacks(sq_id, &ROOK_DIRECTIONS, occupancy)
+            );
+            assert_eq!(
+                attacks(bishop, sq_id, occupancy),
+                sliding_attacks(sq_id, &BISHOP_DIRECTIONS, occupancy)
+            );
+        }
+    }
+}
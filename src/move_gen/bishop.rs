@@ -1,57 +1,16 @@
-use super::internal::{
-    get_ij_from_sq_id, intersect, is_inside_board, try_generate_move_in_direction,
-};
-use super::{internal, MoveGenOpts, MoveGenPerspective};
-use super::{BitboardMoveGenerator, MovesMap, PieceAndMoves};
-use crate::chess::bitboard::{self, BitArraySize, PlayerBitboard};
+use super::move_list::MoveList;
+use super::{attacks, target_squares, BitboardMoveGenerator, MoveGenOpts, MoveGenPerspective};
+use crate::chess::bitboard::{BitArraySize, BitB64, PlayerBitboard, EMPTY_BOARD};
 use crate::chess::position::Position;
-use crate::chess::PlayerColor;
-use crate::chess::{
-    bitboard::{BitB64, BitboardMove, EMPTY_BOARD},
-    PieceType,
-};
-
-use std::collections::HashMap;
+use crate::chess::PieceType;
 
 pub fn compute_single_bishop_attacking_moves(
     ally_pieces: &PlayerBitboard,
     enemy_pieces: &PlayerBitboard,
     id: u8,
 ) -> BitB64 {
-    let mut cur_bishop_moves = EMPTY_BOARD;
-    let mut dir_blockedness = vec![
-        false, // upleft
-        false, // upright
-        false, // downleft
-        false, // downright
-    ];
-
-    let (i0, j0) = get_ij_from_sq_id(id as i8);
-    // Try all possible distances (1..7 in all four diagonals):
-    for i in 1..8 {
-        // upleft, upright, downleft, downright
-        let all_dir_ids = vec![
-            (i0 + i, j0 - i),
-            (i0 + i, j0 + i),
-            (i0 - i, j0 - i),
-            (i0 - i, j0 + i),
-        ];
-        let mut should_keep_trying = false;
-        for (dir_sq_id, mut dir_blocked) in all_dir_ids.iter().zip(dir_blockedness.iter_mut()) {
-            try_generate_move_in_direction(
-                *dir_sq_id,
-                ally_pieces,
-                enemy_pieces,
-                &mut dir_blocked,
-                &mut cur_bishop_moves,
-            );
-            should_keep_trying |= !*dir_blocked;
-        }
-        if !should_keep_trying {
-            break;
-        }
-    }
-    cur_bishop_moves
+    let occupancy = ally_pieces.all_pieces() | enemy_pieces.all_pieces();
+    attacks::bishop_attacks(id, occupancy) & !ally_pieces.all_pieces()
 }
 
 pub fn compute_raw_attacking_moves_as_bishop_internal(
@@ -69,29 +28,21 @@ pub fn compute_raw_attacking_moves_as_bishop_internal(
     result
 }
 
-pub fn get_attacking_moves_as_bishop_internal(
+// Pushes the moves of the pieces of |real_type| moving as bishops onto |targets| to |moves|.
+pub fn generate_moves_as_bishop_internal(
     ally_pieces: &PlayerBitboard,
     enemy_pieces: &PlayerBitboard,
     real_type: PieceType,
-) -> MovesMap {
-    let mut result = HashMap::new();
+    targets: BitB64,
+    moves: &mut MoveList,
+) {
     let mut piece_set = *ally_pieces.pieces(real_type);
-    while piece_set != 0 {
-        let id = piece_set.trailing_zeros() as i8;
-        let cur_bishop = u64::nth(id as u8);
-        piece_set ^= cur_bishop; // Remove bishop from the set.
-        let cur_bishop_moves =
-            compute_single_bishop_attacking_moves(ally_pieces, enemy_pieces, id as u8);
-        let moves = internal::bitb64_to_moves_list(id as u8, cur_bishop_moves);
-        result.insert(
-            id as u8,
-            PieceAndMoves {
-                typpe: real_type,
-                moves: moves,
-            },
-        );
+    while piece_set != EMPTY_BOARD {
+        let id = piece_set.trailing_zeros() as u8;
+        piece_set ^= u64::nth(id); // Remove bishop from the set.
+        let cur_bishop_moves = compute_single_bishop_attacking_moves(ally_pieces, enemy_pieces, id);
+        moves.push_regular_moves(id, cur_bishop_moves & targets, real_type);
     }
-    result
 }
 
 pub struct BishopBitboardMoveGenerator {}
@@ -105,19 +56,17 @@ impl BitboardMoveGenerator for BishopBitboardMoveGenerator {
         compute_raw_attacking_moves_as_bishop_internal(ally_pieces, enemy_pieces, PieceType::Bishop)
     }
 
-    fn get_attacking_moves(pos: &Position, opts: MoveGenOpts) -> MovesMap {
+    fn generate_moves(pos: &Position, opts: MoveGenOpts, moves: &mut MoveList) {
         let (ally_pieces, enemy_pieces) = match opts.perspective {
             MoveGenPerspective::MovingPlayer => (pos.pieces_to_move(), pos.enemy_pieces()),
             MoveGenPerspective::WaitingPlayer => (pos.enemy_pieces(), pos.pieces_to_move()),
         };
-        get_attacking_moves_as_bishop_internal(
-            pos.pieces_to_move(),
-            pos.enemy_pieces(),
+        generate_moves_as_bishop_internal(
+            ally_pieces,
+            enemy_pieces,
             PieceType::Bishop,
-        )
-    }
-
-    fn generate_moves(pos: &Position, opts: MoveGenOpts) -> MovesMap {
-        Self::get_attacking_moves(pos, opts)
+            target_squares(ally_pieces, enemy_pieces, opts.mode),
+            moves,
+        );
     }
 }
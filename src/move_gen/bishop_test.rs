@@ -3,7 +3,7 @@ use super::{
     internal::test_utils::{self, *},
     Position,
 };
-use crate::move_gen::{bishop::BishopBitboardMoveGenerator, BitboardMoveGenerator};
+use crate::move_gen::bishop::BishopBitboardMoveGenerator;
 
 use std::collections::HashMap;
 
@@ -13,6 +13,6 @@ fn test_initial_pos() {
     let pos = test_utils::get_initial_position();
     assert_eq!(
         HashMap::new(),
-        BishopBitboardMoveGenerator::generate_moves(&pos)
+        generate_moves_map::<BishopBitboardMoveGenerator>(&pos)
     );
 }
@@ -1,4 +1,3 @@
-use super::MovesMap;
 use crate::chess::{
     bitboard::{BitArraySize, BitB64, BitboardMove, PlayerBitboard, SpecialMoveType, EMPTY_BOARD},
     position::Position,
@@ -78,8 +77,26 @@ pub fn try_generate_move_in_direction(
 
 pub mod test_utils {
     use crate::chess::position::Position;
+    use crate::move_gen::move_list::MoveList;
+    use crate::move_gen::{
+        BitboardMoveGenerator, MoveGenMode, MoveGenOpts, MoveGenPerspective, MovesMap,
+    };
 
     pub fn get_initial_position() -> super::Position {
         Position::new()
     }
+
+    pub fn moving_player_opts() -> MoveGenOpts {
+        MoveGenOpts {
+            perspective: MoveGenPerspective::MovingPlayer,
+            mode: MoveGenMode::AllMoves,
+        }
+    }
+
+    // Moves of the player to move generated by |G|, grouped by origin square.
+    pub fn generate_moves_map<G: BitboardMoveGenerator>(pos: &Position) -> MovesMap {
+        let mut moves = MoveList::new();
+        G::generate_moves(pos, moving_player_opts(), &mut moves);
+        moves.to_moves_map()
+    }
 }
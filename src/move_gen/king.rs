@@ -1,20 +1,17 @@
-use rocket::futures::io::ReuniteError;
-
 use super::internal::intersect;
-use super::{internal::bounded, BitboardMoveGenerator, MovesMap, PieceAndMoves};
-use super::{MoveGenOpts, MoveGenPerspective};
+use super::move_list::{Move, MoveList};
+use super::{attacks, target_squares, BitboardMoveGenerator};
+use super::{MoveGenMode, MoveGenOpts, MoveGenPerspective};
 use crate::chess::bitboard::{self, PlayerBitboard};
-use crate::chess::position::{self, Position, PositionInfo};
+use crate::chess::position::{Position, PositionInfo};
 use crate::chess::{
     bitboard::{BitArraySize, SpecialMoveType},
     PlayerColor,
 };
 use crate::chess::{
-    bitboard::{BitB64, BitboardMove, EMPTY_BOARD, FULL_BOARD},
+    bitboard::{BitB64, EMPTY_BOARD},
     PieceType,
 };
-use crate::move_gen::internal::{self, bitb64_to_moves_list, get_ij_from_sq_id, get_sq_id_from_ij};
-use std::collections::HashMap;
 
 pub struct KingBitboardMoveGenerator {}
 
@@ -24,8 +21,12 @@ fn long_castle_not_blocked_by_pieces(
     p_to_move: PlayerColor,
 ) -> bool {
     let mask = match p_to_move {
-        PlayerColor::White => u64::nth(bitboard::B1) | u64::nth(bitboard::C1),
-        PlayerColor::Black => u64::nth(bitboard::B8) | u64::nth(bitboard::C8),
+        PlayerColor::White => {
+            u64::nth(bitboard::B1) | u64::nth(bitboard::C1) | u64::nth(bitboard::D1)
+        }
+        PlayerColor::Black => {
+            u64::nth(bitboard::B8) | u64::nth(bitboard::C8) | u64::nth(bitboard::D8)
+        }
     };
     intersect(ally_pieces.all_pieces() | enemy_pieces.all_pieces(), mask)
 }
@@ -124,67 +125,38 @@ fn compute_raw_king_attacking_moves_internal(
     ally_pieces: &PlayerBitboard,
     enemy_pieces: &PlayerBitboard,
 ) -> BitB64 {
-    let king: u64 = ally_pieces.king;
-    let id: i8 = king.trailing_zeros() as i8;
-    let mut result = EMPTY_BOARD;
-    let (i0, j0) = get_ij_from_sq_id(id);
-    for i in [-1i8, 0i8, 1i8] {
-        for j in [-1i8, 0i8, 1i8] {
-            if i == 0 && j == 0 {
-                continue;
-            }
-            let (i1, j1) = (i0 + i, j0 + j);
-            if !internal::is_inside_board(i1, j1) {
-                continue;
-            }
-            result |= u64::nth(get_sq_id_from_ij(i1, j1) as u8);
-        }
-    }
-    result &= u64::compl(ally_pieces.all_pieces());
-    result
+    let id = ally_pieces.king.trailing_zeros() as u8;
+    attacks::king_attacks(id) & !ally_pieces.all_pieces()
 }
 
-fn get_attacking_moves_internal(
+fn generate_moves_internal(
     ally_pieces: &PlayerBitboard,
     enemy_pieces: &PlayerBitboard,
-) -> MovesMap {
+    targets: BitB64,
+    moves: &mut MoveList,
+) {
     let king: u64 = ally_pieces.king;
     if king == EMPTY_BOARD {
         println!("Attemped to generate moves for king but there is no king!");
-        return HashMap::new();
+        return;
     }
-
-    let mut result = HashMap::new();
-    let id: i8 = king.trailing_zeros() as i8;
-
-    let moves = internal::bitb64_to_moves_list(
-        id as u8,
-        compute_raw_king_attacking_moves_internal(ally_pieces, enemy_pieces),
+    let id = king.trailing_zeros() as u8;
+    moves.push_regular_moves(
+        id,
+        compute_raw_king_attacking_moves_internal(ally_pieces, enemy_pieces) & targets,
+        PieceType::King,
     );
-    if !moves.is_empty() {
-        result.insert(
-            id as u8,
-            PieceAndMoves {
-                typpe: PieceType::King,
-                moves,
-            },
-        );
-    }
-    result
 }
 
-fn generate_moves_internal(
+fn generate_castling_moves(
     ally_pieces: &PlayerBitboard,
     enemy_pieces: &PlayerBitboard,
     p_to_move: PlayerColor,
     pos_info: &PositionInfo,
     enemy_attacked_squares: BitB64,
-) -> MovesMap {
-    let king: u64 = ally_pieces.king;
-    let id = king.trailing_zeros() as u8;
-
-    let mut result = get_attacking_moves_internal(ally_pieces, enemy_pieces);
-
+    moves: &mut MoveList,
+) {
+    let id = ally_pieces.king.trailing_zeros() as u8;
     if short_castle_valid(
         ally_pieces,
         enemy_pieces,
@@ -192,26 +164,12 @@ fn generate_moves_internal(
         pos_info,
         enemy_attacked_squares,
     ) {
-        let short_castle_sq_id = get_short_castle_sq_id(p_to_move);
-        if let Some(x) = result.get_mut(&id) {
-            x.moves.push(BitboardMove {
-                from: id,
-                to: short_castle_sq_id,
-                sp_move_type: SpecialMoveType::ShortCastle,
-            });
-        } else {
-            result.insert(
-                id,
-                PieceAndMoves {
-                    typpe: PieceType::King,
-                    moves: vec![BitboardMove {
-                        from: id,
-                        to: short_castle_sq_id,
-                        sp_move_type: SpecialMoveType::ShortCastle,
-                    }],
-                },
-            );
-        }
+        moves.push(Move::new(
+            id,
+            get_short_castle_sq_id(p_to_move),
+            SpecialMoveType::ShortCastle,
+            PieceType::King,
+        ));
     }
     if long_castle_valid(
         ally_pieces,
@@ -220,29 +178,13 @@ fn generate_moves_internal(
         pos_info,
         enemy_attacked_squares,
     ) {
-        let long_castle_sq_id = get_long_castle_sq_id(p_to_move);
-        if let Some(x) = result.get_mut(&id) {
-            x.moves.push(BitboardMove {
-                from: id,
-                to: long_castle_sq_id,
-                sp_move_type: SpecialMoveType::LongCastle,
-            });
-        } else {
-            let long_castle_sq_id = get_long_castle_sq_id(p_to_move);
-            result.insert(
-                id,
-                PieceAndMoves {
-                    typpe: PieceType::King,
-                    moves: vec![BitboardMove {
-                        from: id,
-                        to: long_castle_sq_id,
-                        sp_move_type: SpecialMoveType::ShortCastle,
-                    }],
-                },
-            );
-        }
+        moves.push(Move::new(
+            id,
+            get_long_castle_sq_id(p_to_move),
+            SpecialMoveType::LongCastle,
+            PieceType::King,
+        ));
     }
-    result
 }
 
 impl BitboardMoveGenerator for KingBitboardMoveGenerator {
@@ -254,15 +196,11 @@ impl BitboardMoveGenerator for KingBitboardMoveGenerator {
         compute_raw_king_attacking_moves_internal(ally_pieces, enemy_pieces)
     }
 
-    fn get_attacking_moves(pos: &Position, opts: MoveGenOpts) -> MovesMap {
-        let (ally_pieces, enemy_pieces) = match opts.perspective {
-            MoveGenPerspective::MovingPlayer => (pos.pieces_to_move(), pos.enemy_pieces()),
-            MoveGenPerspective::WaitingPlayer => (pos.enemy_pieces(), pos.pieces_to_move()),
+    fn generate_moves(pos: &Position, opts: MoveGenOpts, moves: &mut MoveList) {
+        let enemy_perspective = match opts.perspective {
+            MoveGenPerspective::MovingPlayer => MoveGenPerspective::WaitingPlayer,
+            MoveGenPerspective::WaitingPlayer => MoveGenPerspective::MovingPlayer,
         };
-        get_attacking_moves_internal(ally_pieces, enemy_pieces)
-    }
-
-    fn generate_moves(pos: &Position, opts: MoveGenOpts) -> MovesMap {
         let (ally_pieces, enemy_pieces, p_to_move) = match opts.perspective {
             MoveGenPerspective::MovingPlayer => (
                 pos.pieces_to_move(),
@@ -275,12 +213,18 @@ impl BitboardMoveGenerator for KingBitboardMoveGenerator {
                 pos.waiting_player(),
             ),
         };
-        generate_moves_internal(
-            ally_pieces,
-            enemy_pieces,
-            p_to_move,
-            &pos.position_info,
-            pos.get_raw_attacked_squares(&opts.perspective),
-        )
+        let targets = target_squares(ally_pieces, enemy_pieces, opts.mode);
+        generate_moves_internal(ally_pieces, enemy_pieces, targets, moves);
+        // Castling never captures, which spares computing the attacked squares.
+        if opts.mode == MoveGenMode::AllMoves && ally_pieces.king != EMPTY_BOARD {
+            generate_castling_moves(
+                ally_pieces,
+                enemy_pieces,
+                p_to_move,
+                &pos.position_info,
+                pos.get_raw_attacked_squares(&enemy_perspective),
+                moves,
+            );
+        }
     }
 }
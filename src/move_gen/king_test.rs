@@ -2,7 +2,7 @@ use super::{
     internal::test_utils::{self, *},
     king, Position,
 };
-use crate::move_gen::{king::KingBitboardMoveGenerator, BitboardMoveGenerator};
+use crate::move_gen::king::KingBitboardMoveGenerator;
 
 use std::collections::HashMap;
 
@@ -12,6 +12,6 @@ fn test_initial_pos() {
     let pos = test_utils::get_initial_position();
     assert_eq!(
         HashMap::new(),
-        KingBitboardMoveGenerator::generate_moves(&pos)
+        generate_moves_map::<KingBitboardMoveGenerator>(&pos)
     );
 }
@@ -1,5 +1,8 @@
 use super::{
-    internal::test_utils::{self, *},
+    internal::{
+        bitb64_to_moves_list,
+        test_utils::{self, *},
+    },
     king, Position,
 };
 
@@ -7,9 +10,7 @@ use crate::chess::{
     bitboard::{BitArraySize, A3, B1, C3, F3, G1, H3},
     PieceType,
 };
-use crate::move_gen::{
-    knight::KnightBitboardMoveGenerator, BitboardMoveGenerator, MovesMap, PieceAndMoves,
-};
+use crate::move_gen::{knight::KnightBitboardMoveGenerator, MovesMap, PieceAndMoves};
 
 use std::collections::HashMap;
 
@@ -23,17 +24,17 @@ fn test_initial_pos() {
                 B1,
                 PieceAndMoves {
                     typpe: PieceType::Knight,
-                    moves: u64::nth(A3) | u64::nth(C3)
+                    moves: bitb64_to_moves_list(B1, u64::nth(A3) | u64::nth(C3)),
                 }
             ),
             (
                 G1,
                 PieceAndMoves {
                     typpe: PieceType::Knight,
-                    moves: u64::nth(F3) | u64::nth(H3)
+                    moves: bitb64_to_moves_list(G1, u64::nth(F3) | u64::nth(H3)),
                 }
             ),
         ]),
-        KnightBitboardMoveGenerator::generate_moves(&pos)
+        generate_moves_map::<KnightBitboardMoveGenerator>(&pos)
     );
 }
@@ -0,0 +1,127 @@
+// Legality of pseudolegal moves, without making them. The checkers, the pinned pieces and the
+// squares attacked around the king are computed once per position, after which checking a
+// move only takes a few bitboard operations.
+use super::attacks::{attacked_squares, attackers_to, between, bishop_attacks, line, rook_attacks};
+use super::move_list::Move;
+use crate::chess::bitboard::{BitArraySize, BitB64, SpecialMoveType, EMPTY_BOARD, FULL_BOARD};
+use crate::chess::position::Position;
+use crate::chess::PieceType;
+
+pub struct CheckInfo {
+    king_sq: u8,
+    // Enemy pieces giving check to the player to move.
+    pub checkers: BitB64,
+    // Pieces of the player to move that cannot leave the line between their king and an enemy
+    // slider.
+    pub pinned: BitB64,
+    // Destinations allowed to pieces other than the king: anywhere when not in check, the
+    // checker or a square between it and the king in single check, nowhere in double check.
+    pub evasion_mask: BitB64,
+    // Squares attacked by the enemy, as if the king of the player to move was not on the
+    // board, so that it cannot step back along the line of a checking slider.
+    king_danger: BitB64,
+}
+
+impl CheckInfo {
+    pub fn new(position: &Position) -> CheckInfo {
+        let allies = position.pieces_to_move();
+        let enemies = position.enemy_pieces();
+        let enemy_color = position.waiting_player();
+        let occupancy = allies.all_pieces() | enemies.all_pieces();
+        let king_sq = allies.king.trailing_zeros() as u8;
+        if allies.king == EMPTY_BOARD {
+            // Only happens in test positions. Nothing is pinned and the king cannot be checked.
+            return CheckInfo {
+                king_sq,
+                checkers: EMPTY_BOARD,
+                pinned: EMPTY_BOARD,
+                evasion_mask: FULL_BOARD,
+                king_danger: EMPTY_BOARD,
+            };
+        }
+
+        let checkers = attackers_to(king_sq, enemies, enemy_color, occupancy);
+        let evasion_mask = match checkers.count_ones() {
+            0 => FULL_BOARD,
+            1 => checkers | between(king_sq, checkers.trailing_zeros() as u8),
+            _ => EMPTY_BOARD,
+        };
+
+        // Enemy sliders that would attack the king on an empty board pin the only piece
+        // standing between them and the king, if it is ours.
+        let snipers = attackers_to(king_sq, enemies, enemy_color, EMPTY_BOARD)
+            & (enemies.bishops | enemies.rooks | enemies.queens);
+        let mut pinned = EMPTY_BOARD;
+        let mut sniper_set = snipers;
+        while sniper_set != EMPTY_BOARD {
+            let sniper_sq = sniper_set.trailing_zeros() as u8;
+            sniper_set ^= u64::nth(sniper_sq);
+            let blockers = between(king_sq, sniper_sq) & occupancy;
+            if blockers.count_ones() == 1 {
+                pinned |= blockers & allies.all_pieces();
+            }
+        }
+
+        CheckInfo {
+            king_sq,
+            checkers,
+            pinned,
+            evasion_mask,
+            king_danger: attacked_squares(enemies, enemy_color, occupancy & !allies.king),
+        }
+    }
+
+    pub fn in_check(&self) -> bool {
+        self.checkers != EMPTY_BOARD
+    }
+
+    // Whether |mv|, a pseudolegal move of the player to move in |position|, keeps its king
+    // out of check.
+    pub fn is_legal(&self, position: &Position, mv: &Move) -> bool {
+        let (from, to) = (mv.from(), mv.to());
+        if mv.piece_type() == PieceType::King {
+            // The generator already checks the squares crossed when castling.
+            return matches!(
+                mv.sp_move_type(),
+                SpecialMoveType::ShortCastle | SpecialMoveType::LongCastle
+            ) || self.king_danger & u64::nth(to) == EMPTY_BOARD;
+        }
+        if matches!(
+            mv.sp_move_type(),
+            SpecialMoveType::EnPassantLeft | SpecialMoveType::EnPassantRight
+        ) {
+            return self.is_en_passant_legal(position, mv);
+        }
+        self.evasion_mask & u64::nth(to) != EMPTY_BOARD && self.respects_pin(from, to)
+    }
+
+    // Pinned pieces may only move along the line of the pin.
+    fn respects_pin(&self, from: u8, to: u8) -> bool {
+        self.pinned & u64::nth(from) == EMPTY_BOARD
+            || line(self.king_sq, from) & u64::nth(to) != EMPTY_BOARD
+    }
+
+    // En passant removes two pawns from the rank of the capturing pawn, which may expose the
+    // king to a slider that no pin accounts for. The sliders are looked at again with both
+    // pawns gone.
+    fn is_en_passant_legal(&self, position: &Position, mv: &Move) -> bool {
+        let (from, to) = (mv.from(), mv.to());
+        // The captured pawn stands next to the capturing one, on the destination file.
+        let captured_sq = from - from % 8 + to % 8;
+        // Capturing the pawn that gives check is an evasion too.
+        let evades = self.evasion_mask & (u64::nth(to) | u64::nth(captured_sq)) != EMPTY_BOARD;
+        if !evades || !self.respects_pin(from, to) {
+            return false;
+        }
+        let enemies = position.enemy_pieces();
+        let occupancy = (position.pieces_to_move().all_pieces() | enemies.all_pieces())
+            ^ u64::nth(from)
+            ^ u64::nth(captured_sq)
+            | u64::nth(to);
+        let rook_attackers =
+            rook_attacks(self.king_sq, occupancy) & (enemies.rooks | enemies.queens);
+        let bishop_attackers =
+            bishop_attacks(self.king_sq, occupancy) & (enemies.bishops | enemies.queens);
+        rook_attackers | bishop_attackers == EMPTY_BOARD
+    }
+}
@@ -0,0 +1,93 @@
+use super::legal::CheckInfo;
+use crate::chess::bitboard::{BitArraySize, BitB64, C1, D2, E4};
+use crate::chess::position::Position;
+use crate::chess::PieceType;
+
+fn legal_moves(fen: &str) -> Vec<String> {
+    let mut result: Vec<String> = Position::from_fen(fen)
+        .unwrap()
+        .legal_continuations()
+        .iter()
+        .map(|mv| mv.to_coordinates())
+        .collect();
+    result.sort();
+    result
+}
+
+fn non_king_moves(fen: &str) -> Vec<String> {
+    let mut result: Vec<String> = Position::from_fen(fen)
+        .unwrap()
+        .legal_continuations()
+        .iter()
+        .filter(|mv| mv.piece_type() != PieceType::King)
+        .map(|mv| mv.to_coordinates())
+        .collect();
+    result.sort();
+    result
+}
+
+fn squares(sq_ids: &[u8]) -> BitB64 {
+    sq_ids
+        .iter()
+        .fold(0, |result, sq_id| result | u64::nth(*sq_id))
+}
+
+#[test]
+fn test_pinned_pieces() {
+    // The knight is pinned by the bishop and the rook by the queen. The pawn on e4 is not:
+    // the black pawn on e6 stands between it and the rook.
+    let fen = "4r2k/8/4p3/8/4P3/2b5/3N4/q1R1K3 w - - 0 1";
+    let position = Position::from_fen(fen).unwrap();
+    assert_eq!(position.pinned_pieces(), squares(&[C1, D2]));
+    let check_info = CheckInfo::new(&position);
+    assert!(!check_info.in_check());
+    assert_eq!(check_info.pinned & u64::nth(E4), 0);
+
+    // A pinned knight never moves, a pinned rook moves along the pin.
+    assert!(non_king_moves(fen).iter().all(|mv| !mv.starts_with("d2")));
+    let rook_moves: Vec<String> = non_king_moves(fen)
+        .into_iter()
+        .filter(|mv| mv.starts_with("c1"))
+        .collect();
+    assert_eq!(rook_moves, vec!["c1a1", "c1b1", "c1d1"]);
+}
+
+#[test]
+fn test_single_check_evasions() {
+    // Only blocking on e2 helps, besides moving the king.
+    let fen = "4r2k/8/8/8/8/8/R7/4K3 w - - 0 1";
+    let check_info = CheckInfo::new(&Position::from_fen(fen).unwrap());
+    assert!(check_info.in_check());
+    assert_eq!(non_king_moves(fen), vec!["a2e2"]);
+    assert!(!legal_moves(fen).contains(&"e1e2".to_string()));
+
+    // The king cannot step back along the line of the checking rook.
+    let fen = "8/8/8/3k4/8/8/8/3RK3 b - - 0 1";
+    assert!(!legal_moves(fen).contains(&"d5d6".to_string()));
+    assert!(legal_moves(fen).contains(&"d5e6".to_string()));
+}
+
+#[test]
+fn test_double_check_only_allows_king_moves() {
+    // The rook on a2 could block the e-file but not the knight.
+    let fen = "4r2k/8/8/8/8/5n2/R7/4K3 w - - 0 1";
+    let check_info = CheckInfo::new(&Position::from_fen(fen).unwrap());
+    assert_eq!(check_info.checkers.count_ones(), 2);
+    assert!(non_king_moves(fen).is_empty());
+    assert!(!legal_moves(fen).is_empty());
+}
+
+#[test]
+fn test_en_passant_evasions() {
+    // Capturing the checking pawn en passant gets out of check.
+    assert_eq!(
+        non_king_moves("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1"),
+        vec!["e4d3"]
+    );
+
+    // The pawn on e4 is not pinned, but capturing would open the rank to the rook.
+    assert_eq!(
+        non_king_moves("8/8/8/8/k2Pp2R/8/8/4K3 b - d3 0 1"),
+        vec!["e4e3"]
+    );
+}
@@ -1,4 +1,7 @@
+pub mod attacks;
 pub mod internal;
+pub mod legal;
+pub mod move_list;
 
 pub mod bishop;
 pub mod king;
@@ -7,14 +10,17 @@ pub mod pawn;
 pub mod queen;
 pub mod rook;
 
+mod attacks_test;
 mod bishop_test;
 mod king_test;
 mod knight_test;
+mod legal_test;
+mod move_list_test;
 mod pawn_test;
 mod queen_test;
 mod rook_test;
 
-use crate::chess::bitboard::{BitArraySize, PlayerBitboard, SpecialMoveType};
+use crate::chess::bitboard::{BitArraySize, PlayerBitboard};
 use crate::chess::position::Position;
 use crate::chess::{
     bitboard::{BitB64, BitboardMove},
@@ -24,6 +30,7 @@ use crate::chess::{
 use std::cmp::Eq;
 
 use internal::{intersect, is_inside_board};
+use move_list::MoveList;
 use std::collections::HashMap;
 
 // Public exports below.
@@ -41,30 +48,35 @@ pub enum MoveGenPerspective {
     WaitingPlayer,
 }
 
+#[derive(Clone, PartialEq, Eq, Debug, Copy)]
+pub enum MoveGenMode {
+    AllMoves,
+    // Captures (en passant included) and promotions, as searched by the quiescence search.
+    CapturesAndPromotions,
+}
+
+#[derive(Clone, Copy)]
 pub struct MoveGenOpts {
     pub perspective: MoveGenPerspective,
+    pub mode: MoveGenMode,
 }
 
 pub trait BitboardMoveGenerator {
     fn get_raw_attacking_moves(pos: &Position, opts: MoveGenOpts) -> BitB64;
 
-    fn generate_moves(pos: &Position, opts: MoveGenOpts) -> MovesMap;
-
-    fn get_attacking_moves(pos: &Position, opts: MoveGenOpts) -> MovesMap;
+    // Pushes the pseudolegal moves allowed by |opts| to |moves|.
+    fn generate_moves(pos: &Position, opts: MoveGenOpts, moves: &mut MoveList);
 }
 
-// Merges two move maps. The second one is borrowed and freed, the first one lives.
-pub fn merge_moves_map(input: MovesMap, output: &mut MovesMap) {
-    for (sq_id, input_pc_and_moves) in input.iter() {
-        // Maybe extend an existing entry.
-        if let Some(output_pc_and_moves) = output.get_mut(sq_id) {
-            output_pc_and_moves
-                .moves
-                .extend(input_pc_and_moves.moves.iter());
-        } else {
-            // Create a new entry.
-            output.insert(*sq_id, input_pc_and_moves.clone());
-        }
+// Squares that pieces of |ally_pieces| may move to, or capture on, under |mode|.
+pub fn target_squares(
+    ally_pieces: &PlayerBitboard,
+    enemy_pieces: &PlayerBitboard,
+    mode: MoveGenMode,
+) -> BitB64 {
+    match mode {
+        MoveGenMode::AllMoves => !ally_pieces.all_pieces(),
+        MoveGenMode::CapturesAndPromotions => enemy_pieces.all_pieces(),
     }
 }
 
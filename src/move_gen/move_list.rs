@@ -0,0 +1,211 @@
+use super::{MovesMap, PieceAndMoves};
+use crate::chess::bitboard::{BitArraySize, BitB64, BitboardMove, SpecialMoveType, EMPTY_BOARD};
+use crate::chess::{ChessPiece, PieceType, PlayerColor};
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+// No legal position has more than 218 moves.
+pub const MAX_MOVES: usize = 256;
+
+const SQUARE_BITS: u32 = 0x3f;
+const TO_SHIFT: u32 = 6;
+const SP_MOVE_TYPE_SHIFT: u32 = 12;
+const PIECE_SHIFT: u32 = 16;
+
+// Indexed by the encoded values, in declaration order.
+const SP_MOVE_TYPES: [SpecialMoveType; 9] = [
+    SpecialMoveType::RegularMove,
+    SpecialMoveType::ShortCastle,
+    SpecialMoveType::LongCastle,
+    SpecialMoveType::PromotionToKnight,
+    SpecialMoveType::PromotionToBishop,
+    SpecialMoveType::PromotionToRook,
+    SpecialMoveType::PromotionToQueen,
+    SpecialMoveType::EnPassantLeft,
+    SpecialMoveType::EnPassantRight,
+];
+const PIECE_TYPES: [PieceType; 6] = [
+    PieceType::Pawn,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::Queen,
+    PieceType::King,
+];
+
+// A move packed in 32 bits: origin square in bits 0-5, destination in bits 6-11, special
+// move type in bits 12-15 and the type of the moving piece in bits 16-18. The color of the
+// piece is the one of the player to move.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Move(u32);
+
+impl Move {
+    pub fn new(from: u8, to: u8, sp_move_type: SpecialMoveType, piece: PieceType) -> Move {
+        Move(
+            from as u32
+                | (to as u32) << TO_SHIFT
+                | (sp_move_type as u32) << SP_MOVE_TYPE_SHIFT
+                | (piece as u32) << PIECE_SHIFT,
+        )
+    }
+
+    // The packed move, for tables that store moves in fewer bits. Only the lowest 19 bits can
+    // be set.
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    pub fn from_bits(bits: u32) -> Move {
+        Move(bits)
+    }
+
+    pub fn from_bitboard_move(mv: &BitboardMove, piece: PieceType) -> Move {
+        Move::new(mv.from, mv.to, mv.sp_move_type, piece)
+    }
+
+    pub fn from(&self) -> u8 {
+        (self.0 & SQUARE_BITS) as u8
+    }
+
+    pub fn to(&self) -> u8 {
+        (self.0 >> TO_SHIFT & SQUARE_BITS) as u8
+    }
+
+    pub fn sp_move_type(&self) -> SpecialMoveType {
+        SP_MOVE_TYPES[(self.0 >> SP_MOVE_TYPE_SHIFT & 0xf) as usize]
+    }
+
+    pub fn piece_type(&self) -> PieceType {
+        PIECE_TYPES[(self.0 >> PIECE_SHIFT & 0x7) as usize]
+    }
+
+    pub fn piece(&self, color: PlayerColor) -> ChessPiece {
+        ChessPiece {
+            typpe: self.piece_type(),
+            color,
+        }
+    }
+
+    pub fn bitboard_move(&self) -> BitboardMove {
+        BitboardMove {
+            from: self.from(),
+            to: self.to(),
+            sp_move_type: self.sp_move_type(),
+        }
+    }
+
+    pub fn to_coordinates(&self) -> String {
+        self.bitboard_move().to_coordinates()
+    }
+}
+
+impl fmt::Debug for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {:?}",
+            self.piece_type(),
+            self.to_coordinates(),
+            self.sp_move_type()
+        )
+    }
+}
+
+// Fixed-capacity list of moves living on the stack, so that generating moves does not
+// allocate. Derefs to a slice of the moves pushed so far.
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    pub fn new() -> MoveList {
+        MoveList {
+            moves: [Move::default(); MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, mv: Move) {
+        self.moves[self.len] = mv;
+        self.len += 1;
+    }
+
+    // Pushes a regular move of |piece| from |from| to every square in |targets|.
+    pub fn push_regular_moves(&mut self, from: u8, mut targets: BitB64, piece: PieceType) {
+        while targets != EMPTY_BOARD {
+            let to = targets.trailing_zeros() as u8;
+            targets ^= u64::nth(to);
+            self.push(Move::new(from, to, SpecialMoveType::RegularMove, piece));
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    // Keeps the moves for which |keep| returns true, in their original order.
+    pub fn retain(&mut self, mut keep: impl FnMut(&Move) -> bool) {
+        let mut kept = 0;
+        for i in 0..self.len {
+            if keep(&self.moves[i]) {
+                self.moves[kept] = self.moves[i];
+                kept += 1;
+            }
+        }
+        self.len = kept;
+    }
+
+    // The moves grouped by origin square, as the server reports them.
+    pub fn to_moves_map(&self) -> MovesMap {
+        let mut result = MovesMap::new();
+        for mv in self.iter() {
+            result
+                .entry(mv.from())
+                .or_insert_with(|| PieceAndMoves {
+                    typpe: mv.piece_type(),
+                    moves: vec![],
+                })
+                .moves
+                .push(mv.bitboard_move());
+        }
+        result
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        MoveList::new()
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &[Move] {
+        &self.moves[..self.len]
+    }
+}
+
+impl DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut [Move] {
+        &mut self.moves[..self.len]
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,65 @@
+use super::move_list::{Move, MoveList};
+use crate::chess::bitboard::{SpecialMoveType, A1, E1, E2, E4, E7, E8, G1, H8};
+use crate::chess::position::Position;
+use crate::chess::PieceType;
+
+#[test]
+fn test_move_encoding() {
+    let mv = Move::new(E7, E8, SpecialMoveType::PromotionToQueen, PieceType::Pawn);
+    assert_eq!(mv.from(), E7);
+    assert_eq!(mv.to(), E8);
+    assert_eq!(mv.sp_move_type(), SpecialMoveType::PromotionToQueen);
+    assert_eq!(mv.piece_type(), PieceType::Pawn);
+    assert_eq!(mv.to_coordinates(), "e7e8q");
+
+    let mv = Move::new(H8, A1, SpecialMoveType::EnPassantRight, PieceType::King);
+    assert_eq!((mv.from(), mv.to()), (H8, A1));
+    assert_eq!(mv.sp_move_type(), SpecialMoveType::EnPassantRight);
+    assert_eq!(mv.piece_type(), PieceType::King);
+    assert_eq!(
+        Move::from_bitboard_move(&mv.bitboard_move(), PieceType::King),
+        mv
+    );
+}
+
+#[test]
+fn test_move_list() {
+    let mut moves = MoveList::new();
+    assert!(moves.is_empty());
+    moves.push(Move::new(
+        E1,
+        G1,
+        SpecialMoveType::ShortCastle,
+        PieceType::King,
+    ));
+    moves.push_regular_moves(E2, 1 << E4 | 1 << (E4 - 8), PieceType::Pawn);
+    assert_eq!(moves.len(), 3);
+    assert_eq!(moves[1].to_coordinates(), "e2e3");
+
+    let moves_map = moves.to_moves_map();
+    assert_eq!(moves_map.len(), 2);
+    assert_eq!(moves_map[&E2].typpe, PieceType::Pawn);
+    assert_eq!(moves_map[&E2].moves.len(), 2);
+
+    moves.retain(|mv| mv.piece_type() == PieceType::Pawn);
+    assert_eq!(moves.len(), 2);
+    assert_eq!(moves[0].to_coordinates(), "e2e3");
+    moves.clear();
+    assert!(moves.is_empty());
+}
+
+#[test]
+fn test_legal_continuations_fill_the_list() {
+    let moves = Position::new().legal_continuations();
+    assert_eq!(moves.len(), 20);
+    assert_eq!(
+        moves
+            .iter()
+            .filter(|mv| mv.piece_type() == PieceType::Knight)
+            .count(),
+        4
+    );
+    assert!(moves
+        .iter()
+        .all(|mv| Position::new().piece_at(mv.from()).unwrap().typpe == mv.piece_type()));
+}
@@ -1,60 +1,25 @@
-use rocket::catcher::Result;
-
-use super::internal::{self, bitb64_to_moves_list};
+use super::attacks;
 use super::internal::{get_ij_from_sq_id, intersect};
-use super::{BitboardMoveGenerator, MoveGenOpts, MovesMap, PieceAndMoves};
+use super::move_list::{Move, MoveList};
+use super::{BitboardMoveGenerator, MoveGenMode, MoveGenOpts};
 use crate::chess::bitboard::{BitArraySize, PlayerBitboard, SpecialMoveType};
-use crate::chess::position::{self, Position, PositionInfo};
+use crate::chess::position::Position;
 use crate::chess::PlayerColor;
 use crate::chess::{
-    bitboard::{BitB64, BitboardMove, EMPTY_BOARD},
+    bitboard::{BitB64, EMPTY_BOARD, FULL_BOARD},
     PieceType,
 };
 use crate::move_gen::MoveGenPerspective;
-use std::collections::HashMap;
 
 pub struct PawnBitboardMoveGenerator {}
 
+// Diagonal squares in front of the pawn on |id| that intersect |targets|.
 fn compute_raw_single_pawn_attacking_moves(
-    enemy_pieces: &PlayerBitboard,
+    targets: BitB64,
     p_to_move: PlayerColor,
     id: i8,
 ) -> BitB64 {
-    let (_, j) = get_ij_from_sq_id(id);
-    let mut cur_pawn_moves = EMPTY_BOARD;
-
-    let pawn_move_direction: i8 = match p_to_move {
-        PlayerColor::Black => -1,
-        PlayerColor::White => 1,
-    };
-
-    let leftmost_col = match p_to_move {
-        PlayerColor::Black => 7,
-        PlayerColor::White => 0,
-    };
-
-    let rightmost_col = match p_to_move {
-        PlayerColor::Black => 0,
-        PlayerColor::White => 7,
-    };
-
-    let advance_sq_id = (id + pawn_move_direction * 8) as i8;
-    let capture_left_sq_id = advance_sq_id - pawn_move_direction;
-    if internal::bounded(capture_left_sq_id, 0, 63) {
-        let sq_capture_left = u64::nth((capture_left_sq_id) as u8);
-        if j != leftmost_col && intersect(sq_capture_left, enemy_pieces.all_pieces()) {
-            cur_pawn_moves |= sq_capture_left;
-        }
-    }
-
-    let capture_right_sq_id = advance_sq_id + pawn_move_direction;
-    if internal::bounded(capture_right_sq_id, 0, 63) {
-        let sq_capture_right = u64::nth((capture_right_sq_id) as u8);
-        if j != rightmost_col && intersect(sq_capture_right, enemy_pieces.all_pieces()) {
-            cur_pawn_moves |= sq_capture_right;
-        }
-    }
-    cur_pawn_moves
+    attacks::pawn_attacks(p_to_move, id as u8) & targets
 }
 
 pub fn compute_pawn_attacking_moves_internal(
@@ -66,67 +31,79 @@ pub fn compute_pawn_attacking_moves_internal(
     let mut piece_set = ally_pieces.pawns;
     while piece_set != 0 {
         let id = piece_set.trailing_zeros() as u8;
-        result |= compute_raw_single_pawn_attacking_moves(enemy_pieces, p_to_move, id as i8);
+        // Pawns attack their diagonals whether or not there is something to capture, which
+        // matters for the squares the king crosses when castling.
+        result |= compute_raw_single_pawn_attacking_moves(FULL_BOARD, p_to_move, id as i8);
         piece_set ^= u64::nth(id);
     }
     result
 }
 
-fn get_attacking_moves_internal(
-    ally_pieces: &PlayerBitboard,
-    enemy_pieces: &PlayerBitboard,
-    p_to_move: PlayerColor,
-) -> MovesMap {
-    let mut result = HashMap::new();
-    let mut pawn_set = ally_pieces.pawns;
-    while pawn_set != EMPTY_BOARD {
-        let id = pawn_set.trailing_zeros() as i8;
-        let cur_pawn: u64 = u64::nth(id as u8);
-        pawn_set ^= cur_pawn;
-        let resulting_moves = internal::bitb64_to_moves_list(
-            id as u8,
-            compute_raw_single_pawn_attacking_moves(enemy_pieces, p_to_move, id),
-        );
-
-        if resulting_moves.len() > 0 {
-            result.insert(
-                id as u8,
-                PieceAndMoves {
-                    typpe: PieceType::Pawn,
-                    moves: resulting_moves,
-                },
-            );
-        }
-    }
-    result
-}
-
-fn add_promotion_moves(moves_list: Vec<BitboardMove>) -> Vec<BitboardMove> {
-    let promotion_types = vec![
+// Pushes a move of the pawn on |from| to every square in |targets|, or the four promotions
+// to each of them if |promotes|.
+fn push_pawn_moves(from: u8, mut targets: BitB64, promotes: bool, moves: &mut MoveList) {
+    let promotion_types = [
         SpecialMoveType::PromotionToBishop,
         SpecialMoveType::PromotionToKnight,
         SpecialMoveType::PromotionToRook,
         SpecialMoveType::PromotionToQueen,
     ];
-    let mut promotion_resulting_moves = vec![];
-    for mv in moves_list.iter() {
-        for promotion_type in promotion_types.iter() {
-            promotion_resulting_moves.push(BitboardMove {
-                from: mv.from,
-                to: mv.to,
-                sp_move_type: *promotion_type,
-            })
+    while targets != EMPTY_BOARD {
+        let to = targets.trailing_zeros() as u8;
+        targets ^= u64::nth(to);
+        if !promotes {
+            moves.push(Move::new(
+                from,
+                to,
+                SpecialMoveType::RegularMove,
+                PieceType::Pawn,
+            ));
+            continue;
+        }
+        for promotion_type in promotion_types {
+            moves.push(Move::new(from, to, promotion_type, PieceType::Pawn));
         }
     }
-    promotion_resulting_moves
+}
+
+// En passant capture available to the pawn on |id| into |en_passant_target|, if any.
+fn get_en_passant_move(
+    p_to_move: PlayerColor,
+    id: i8,
+    en_passant_target: Option<u8>,
+) -> Option<Move> {
+    let target = en_passant_target? as i8;
+    let (_, j) = get_ij_from_sq_id(id);
+    let pawn_move_direction: i8 = match p_to_move {
+        PlayerColor::Black => -1,
+        PlayerColor::White => 1,
+    };
+    let advance_sq_id = id + pawn_move_direction * 8;
+    let (target_i, target_j) = get_ij_from_sq_id(target);
+    if target_i != get_ij_from_sq_id(advance_sq_id).0 || (target_j - j).abs() != 1 {
+        return None;
+    }
+    let sp_move_type = if target == advance_sq_id - pawn_move_direction {
+        SpecialMoveType::EnPassantLeft
+    } else {
+        SpecialMoveType::EnPassantRight
+    };
+    Some(Move::new(
+        id as u8,
+        target as u8,
+        sp_move_type,
+        PieceType::Pawn,
+    ))
 }
 
 fn generate_moves_internal(
     ally_pieces: &PlayerBitboard,
     enemy_pieces: &PlayerBitboard,
     p_to_move: PlayerColor,
-) -> MovesMap {
-    let mut result = HashMap::new();
+    en_passant_target: Option<u8>,
+    mode: MoveGenMode,
+    moves: &mut MoveList,
+) {
     let mut pawn_set = ally_pieces.pawns;
     while pawn_set != EMPTY_BOARD {
         let id = pawn_set.trailing_zeros() as i8;
@@ -154,29 +131,21 @@ fn generate_moves_internal(
         };
         let all_pieces = enemy_pieces.all_pieces() | ally_pieces.all_pieces();
         let piece_in_front = intersect(advance_square, all_pieces);
-        if !piece_in_front {
+        // Pushes are only captures-mode moves when they promote.
+        if !piece_in_front && (mode == MoveGenMode::AllMoves || is_last_row_b4_promotion) {
             cur_pawn_moves |= advance_square;
             let double_adv_sq = u64::nth((double_advance_offset + j) as u8);
             if i == pawns_initial_row && !intersect(double_adv_sq, all_pieces) {
                 cur_pawn_moves |= double_adv_sq;
             }
         }
-        cur_pawn_moves |= compute_raw_single_pawn_attacking_moves(enemy_pieces, p_to_move, id);
-        let resulting_moves = internal::bitb64_to_moves_list(id as u8, cur_pawn_moves);
-        if resulting_moves.len() > 0 {
-            result.insert(
-                id as u8,
-                PieceAndMoves {
-                    typpe: PieceType::Pawn,
-                    moves: match is_last_row_b4_promotion {
-                        true => add_promotion_moves(resulting_moves),
-                        false => resulting_moves,
-                    },
-                },
-            );
+        cur_pawn_moves |=
+            compute_raw_single_pawn_attacking_moves(enemy_pieces.all_pieces(), p_to_move, id);
+        push_pawn_moves(id as u8, cur_pawn_moves, is_last_row_b4_promotion, moves);
+        if let Some(mv) = get_en_passant_move(p_to_move, id, en_passant_target) {
+            moves.push(mv);
         }
     }
-    result
 }
 
 impl BitboardMoveGenerator for PawnBitboardMoveGenerator {
@@ -196,35 +165,29 @@ impl BitboardMoveGenerator for PawnBitboardMoveGenerator {
         compute_pawn_attacking_moves_internal(ally_pieces, enemy_pieces, p_to_move)
     }
 
-    fn get_attacking_moves(pos: &Position, opts: MoveGenOpts) -> MovesMap {
-        let (ally_pieces, enemy_pieces, p_to_move) = match opts.perspective {
-            MoveGenPerspective::MovingPlayer => (
-                pos.pieces_to_move(),
-                pos.enemy_pieces(),
-                pos.player_to_move(),
-            ),
-            MoveGenPerspective::WaitingPlayer => (
-                pos.enemy_pieces(),
-                pos.pieces_to_move(),
-                pos.waiting_player(),
-            ),
-        };
-        get_attacking_moves_internal(ally_pieces, enemy_pieces, p_to_move)
-    }
-
-    fn generate_moves(pos: &Position, opts: MoveGenOpts) -> MovesMap {
-        let (ally_pieces, enemy_pieces, p_to_move) = match opts.perspective {
+    fn generate_moves(pos: &Position, opts: MoveGenOpts, moves: &mut MoveList) {
+        let (ally_pieces, enemy_pieces, p_to_move, en_passant_target) = match opts.perspective {
             MoveGenPerspective::MovingPlayer => (
                 pos.pieces_to_move(),
                 pos.enemy_pieces(),
                 pos.player_to_move(),
+                pos.position_info.en_passant_target_sq_id(),
             ),
+            // The en passant target always belongs to the player to move.
             MoveGenPerspective::WaitingPlayer => (
                 pos.enemy_pieces(),
                 pos.pieces_to_move(),
                 pos.waiting_player(),
+                None,
             ),
         };
-        generate_moves_internal(ally_pieces, enemy_pieces, p_to_move)
+        generate_moves_internal(
+            ally_pieces,
+            enemy_pieces,
+            p_to_move,
+            en_passant_target,
+            opts.mode,
+            moves,
+        );
     }
 }
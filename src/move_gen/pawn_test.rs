@@ -1,5 +1,8 @@
 use super::{
-    internal::test_utils::{self, *},
+    internal::{
+        bitb64_to_moves_list,
+        test_utils::{self, *},
+    },
     king, Position,
 };
 
@@ -10,9 +13,7 @@ use crate::chess::{
     },
     PieceType,
 };
-use crate::move_gen::{
-    pawn::PawnBitboardMoveGenerator, BitboardMoveGenerator, MovesMap, PieceAndMoves,
-};
+use crate::move_gen::{pawn::PawnBitboardMoveGenerator, MovesMap, PieceAndMoves};
 
 use std::collections::HashMap;
 
@@ -26,59 +27,59 @@ fn test_initial_pos() {
                 A2,
                 PieceAndMoves {
                     typpe: PieceType::Pawn,
-                    moves: u64::nth(A3) | u64::nth(A4),
+                    moves: bitb64_to_moves_list(A2, u64::nth(A3) | u64::nth(A4)),
                 }
             ),
             (
                 B2,
                 PieceAndMoves {
                     typpe: PieceType::Pawn,
-                    moves: u64::nth(B3) | u64::nth(B4),
+                    moves: bitb64_to_moves_list(B2, u64::nth(B3) | u64::nth(B4)),
                 }
             ),
             (
                 C2,
                 PieceAndMoves {
                     typpe: PieceType::Pawn,
-                    moves: u64::nth(C3) | u64::nth(C4),
+                    moves: bitb64_to_moves_list(C2, u64::nth(C3) | u64::nth(C4)),
                 }
             ),
             (
                 D2,
                 PieceAndMoves {
                     typpe: PieceType::Pawn,
-                    moves: u64::nth(D3) | u64::nth(D4),
+                    moves: bitb64_to_moves_list(D2, u64::nth(D3) | u64::nth(D4)),
                 }
             ),
             (
                 E2,
                 PieceAndMoves {
                     typpe: PieceType::Pawn,
-                    moves: u64::nth(E3) | u64::nth(E4),
+                    moves: bitb64_to_moves_list(E2, u64::nth(E3) | u64::nth(E4)),
                 }
             ),
             (
                 F2,
                 PieceAndMoves {
                     typpe: PieceType::Pawn,
-                    moves: u64::nth(F3) | u64::nth(F4),
+                    moves: bitb64_to_moves_list(F2, u64::nth(F3) | u64::nth(F4)),
                 }
             ),
             (
                 G2,
                 PieceAndMoves {
                     typpe: PieceType::Pawn,
-                    moves: u64::nth(G3) | u64::nth(G4),
+                    moves: bitb64_to_moves_list(G2, u64::nth(G3) | u64::nth(G4)),
                 }
             ),
             (
                 H2,
                 PieceAndMoves {
                     typpe: PieceType::Pawn,
-                    moves: u64::nth(H3) | u64::nth(H4),
+                    moves: bitb64_to_moves_list(H2, u64::nth(H3) | u64::nth(H4)),
                 }
             ),
         ]),
-        PawnBitboardMoveGenerator::generate_moves(&pos)
+        generate_moves_map::<PawnBitboardMoveGenerator>(&pos)
     );
 }
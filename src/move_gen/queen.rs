@@ -1,11 +1,8 @@
-use super::{bishop, rook, MoveGenOpts, MoveGenPerspective};
-use super::{
-    bishop::BishopBitboardMoveGenerator, rook::RookBitboardMoveGenerator, BitboardMoveGenerator,
-    MovesMap, PieceAndMoves,
-};
+use super::move_list::MoveList;
+use super::{bishop, rook, target_squares, BitboardMoveGenerator, MoveGenOpts, MoveGenPerspective};
 
-use crate::chess::bitboard::{BitArraySize, BitB64, BitboardMove, EMPTY_BOARD};
-use crate::chess::position::{self, Position};
+use crate::chess::bitboard::BitB64;
+use crate::chess::position::Position;
 use crate::chess::PieceType;
 
 pub struct QueenBitboardMoveGenerator {}
@@ -23,24 +20,19 @@ impl BitboardMoveGenerator for QueenBitboardMoveGenerator {
         ) | rook::compute_raw_attacking_moves_as_rook(ally_pieces, enemy_pieces, PieceType::Queen)
     }
 
-    fn get_attacking_moves(pos: &Position, opts: MoveGenOpts) -> MovesMap {
+    fn generate_moves(pos: &Position, opts: MoveGenOpts, moves: &mut MoveList) {
         let (ally_pieces, enemy_pieces) = match opts.perspective {
             MoveGenPerspective::MovingPlayer => (pos.pieces_to_move(), pos.enemy_pieces()),
             MoveGenPerspective::WaitingPlayer => (pos.enemy_pieces(), pos.pieces_to_move()),
         };
-        let mut result = bishop::get_attacking_moves_as_bishop_internal(
+        let targets = target_squares(ally_pieces, enemy_pieces, opts.mode);
+        bishop::generate_moves_as_bishop_internal(
             ally_pieces,
             enemy_pieces,
             PieceType::Queen,
+            targets,
+            moves,
         );
-        super::merge_moves_map(
-            rook::compute_attacking_moves_as_rook(ally_pieces, enemy_pieces, PieceType::Queen),
-            &mut result,
-        );
-        result
-    }
-
-    fn generate_moves(pos: &Position, opts: MoveGenOpts) -> MovesMap {
-        Self::get_attacking_moves(pos, opts)
+        rook::generate_moves_as_rook(ally_pieces, enemy_pieces, PieceType::Queen, targets, moves);
     }
 }
@@ -1,5 +1,5 @@
 use super::internal::test_utils::{self, *};
-use crate::move_gen::{queen::QueenBitboardMoveGenerator, BitboardMoveGenerator};
+use crate::move_gen::queen::QueenBitboardMoveGenerator;
 
 use std::collections::HashMap;
 
@@ -9,6 +9,6 @@ fn test_initial_pos() {
     let pos = test_utils::get_initial_position();
     assert_eq!(
         HashMap::new(),
-        QueenBitboardMoveGenerator::generate_moves(&pos)
+        generate_moves_map::<QueenBitboardMoveGenerator>(&pos)
     );
 }
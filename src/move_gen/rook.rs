@@ -1,12 +1,9 @@
-use super::internal::{self, bitb64_to_moves_list};
-use super::internal::{get_ij_from_sq_id, try_generate_move_in_direction};
-use super::{BitboardMoveGenerator, MoveGenOpts, MoveGenPerspective, MovesMap, PieceAndMoves};
+use super::move_list::MoveList;
+use super::{attacks, target_squares, BitboardMoveGenerator, MoveGenOpts, MoveGenPerspective};
 use crate::chess::bitboard;
-use crate::chess::bitboard::{BitArraySize, BitB64, BitboardMove, PlayerBitboard, EMPTY_BOARD};
+use crate::chess::bitboard::{BitArraySize, BitB64, PlayerBitboard, EMPTY_BOARD};
 use crate::chess::position::Position;
 use crate::chess::{PieceType, PlayerColor};
-use std::collections::hash_map;
-use std::collections::HashMap;
 
 pub struct RookBitboardMoveGenerator {}
 
@@ -29,34 +26,8 @@ pub fn compute_single_rook_attacking_moves(
     enemy_pieces: &PlayerBitboard,
     id: u8,
 ) -> BitB64 {
-    let (i0, j0) = get_ij_from_sq_id(id as i8);
-    let mut cur_rook_moves = EMPTY_BOARD;
-    let mut dir_blockedness = vec![
-        false, // up
-        false, // down
-        false, // righ
-        false, // left
-    ];
-    // Try all possible distances (1..7 in all four diagonals):
-    for i in 1..8 {
-        // up, down, right, left
-        let all_dir_ids = vec![(i0 + i, j0), (i0 - i, j0), (i0, j0 + i), (i0, j0 - i)];
-        let mut should_keep_trying = false;
-        for (dir_sq_id, mut dir_blocked) in all_dir_ids.iter().zip(dir_blockedness.iter_mut()) {
-            try_generate_move_in_direction(
-                *dir_sq_id,
-                ally_pieces,
-                enemy_pieces,
-                &mut dir_blocked,
-                &mut cur_rook_moves,
-            );
-            should_keep_trying |= !*dir_blocked;
-        }
-        if !should_keep_trying {
-            break;
-        }
-    }
-    cur_rook_moves
+    let occupancy = ally_pieces.all_pieces() | enemy_pieces.all_pieces();
+    attacks::rook_attacks(id, occupancy) & !ally_pieces.all_pieces()
 }
 
 pub fn compute_raw_attacking_moves_as_rook(
@@ -74,26 +45,21 @@ pub fn compute_raw_attacking_moves_as_rook(
     result
 }
 
-pub fn compute_attacking_moves_as_rook(
+// Pushes the moves of the pieces of |real_type| moving as rooks onto |targets| to |moves|.
+pub fn generate_moves_as_rook(
     ally_pieces: &PlayerBitboard,
     enemy_pieces: &PlayerBitboard,
     real_type: PieceType,
-) -> MovesMap {
-    let mut result = HashMap::new();
+    targets: BitB64,
+    moves: &mut MoveList,
+) {
     let mut piece_set = *ally_pieces.pieces(real_type);
     while piece_set != EMPTY_BOARD {
         let id = piece_set.trailing_zeros() as u8;
-        let moves = compute_single_rook_attacking_moves(ally_pieces, enemy_pieces, id);
-        result.insert(
-            id,
-            PieceAndMoves {
-                typpe: real_type,
-                moves: bitb64_to_moves_list(id, moves),
-            },
-        );
+        let rook_moves = compute_single_rook_attacking_moves(ally_pieces, enemy_pieces, id);
+        moves.push_regular_moves(id, rook_moves & targets, real_type);
         piece_set ^= u64::nth(id);
     }
-    result
 }
 
 impl BitboardMoveGenerator for RookBitboardMoveGenerator {
@@ -105,11 +71,17 @@ impl BitboardMoveGenerator for RookBitboardMoveGenerator {
         compute_raw_attacking_moves_as_rook(ally_pieces, enemy_pieces, PieceType::Rook)
     }
 
-    fn get_attacking_moves(pos: &Position, opts: MoveGenOpts) -> MovesMap {
-        compute_attacking_moves_as_rook(pos.pieces_to_move(), pos.enemy_pieces(), PieceType::Rook)
-    }
-
-    fn generate_moves(pos: &Position, opts: MoveGenOpts) -> MovesMap {
-        Self::get_attacking_moves(pos, opts)
+    fn generate_moves(pos: &Position, opts: MoveGenOpts, moves: &mut MoveList) {
+        let (ally_pieces, enemy_pieces) = match opts.perspective {
+            MoveGenPerspective::MovingPlayer => (pos.pieces_to_move(), pos.enemy_pieces()),
+            MoveGenPerspective::WaitingPlayer => (pos.enemy_pieces(), pos.pieces_to_move()),
+        };
+        generate_moves_as_rook(
+            ally_pieces,
+            enemy_pieces,
+            PieceType::Rook,
+            target_squares(ally_pieces, enemy_pieces, opts.mode),
+            moves,
+        );
     }
 }
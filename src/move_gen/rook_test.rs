@@ -1,8 +1,15 @@
 use super::{
-    internal::test_utils::{self, *},
+    internal::{
+        bitb64_to_moves_list,
+        test_utils::{self, *},
+    },
     king, Position,
 };
-use crate::move_gen::{rook::RookBitboardMoveGenerator, BitboardMoveGenerator};
+use crate::chess::{
+    bitboard::{BitArraySize, H1, H2, H3},
+    PieceType,
+};
+use crate::move_gen::{rook::RookBitboardMoveGenerator, PieceAndMoves};
 
 use std::collections::HashMap;
 
@@ -12,17 +19,23 @@ fn test_initial_pos() {
     let pos = test_utils::get_initial_position();
     assert_eq!(
         HashMap::new(),
-        RookBitboardMoveGenerator::generate_moves(&pos)
+        generate_moves_map::<RookBitboardMoveGenerator>(&pos)
     );
 }
 
 #[test]
 fn test_h4_h5() {
-    // Initial position has no moves for bishops.
-    let pos = test_utils::get_initial_position();
-    
+    // After 1. h4 h5 the h1 rook can climb up to h3.
+    let pos =
+        Position::from_fen("rnbqkbnr/ppppppp1/8/7p/7P/8/PPPPPPP1/RNBQKBNR w KQkq h6 0 2").unwrap();
     assert_eq!(
-        HashMap::new(),
-        RookBitboardMoveGenerator::generate_moves(&pos)
+        HashMap::from([(
+            H1,
+            PieceAndMoves {
+                typpe: PieceType::Rook,
+                moves: bitb64_to_moves_list(H1, u64::nth(H2) | u64::nth(H3)),
+            }
+        )]),
+        generate_moves_map::<RookBitboardMoveGenerator>(&pos)
     );
 }
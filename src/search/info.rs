@@ -0,0 +1,70 @@
+// Progress reports of a running search, for whoever follows it: the UCI "info" lines, the
+// analysis sessions of the server, logs and tests. Subscribe with
+// MinimaxSearchEvaluator::with_info_callback() or with_info_channel().
+use crate::chess::PlayerColor;
+use crate::move_gen::move_list::Move;
+use crate::search::score::mate_in_moves;
+use std::sync::Arc;
+use std::time::Duration;
+
+// From white's point of view, as the scores of search results.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InfoScore {
+    Centipawns(i32),
+    // Moves to mate: positive when white mates, negative when black does.
+    MateIn(i32),
+}
+
+impl InfoScore {
+    pub fn from_score(score: i32) -> Self {
+        match mate_in_moves(score) {
+            Some(moves) => InfoScore::MateIn(moves),
+            None => InfoScore::Centipawns(score),
+        }
+    }
+
+    // The same score from the point of view of |color|.
+    pub fn relative_to(self, color: PlayerColor) -> Self {
+        match (self, color) {
+            (_, PlayerColor::White) => self,
+            (InfoScore::Centipawns(cp), PlayerColor::Black) => InfoScore::Centipawns(-cp),
+            (InfoScore::MateIn(moves), PlayerColor::Black) => InfoScore::MateIn(-moves),
+        }
+    }
+}
+
+// A line of a completed iteration.
+#[derive(Clone, Debug)]
+pub struct LineInfo {
+    pub depth: u8,
+    // Deepest ply the main thread reached in the iteration, quiescence search included.
+    pub seldepth: u8,
+    // Rank of the line among the MultiPV lines, from 1.
+    pub multipv: usize,
+    pub score: InfoScore,
+    pub pv: Vec<Move>,
+    // Nodes of the whole search so far, by all its threads.
+    pub nodes: u64,
+    pub nps: u64,
+    pub time: Duration,
+    // Permille of the transposition table filled by this search.
+    pub hashfull: u16,
+}
+
+#[derive(Clone, Debug)]
+pub enum SearchInfo {
+    // Sent for every line at the end of each iteration, best line first.
+    Line(LineInfo),
+    // The main thread starts the search of the |number|th root move (from 1) at |depth|. Only
+    // sent once the search has run for CURRMOVE_DELAY: short searches would flood the
+    // subscribers.
+    CurrentMove { depth: u8, mv: Move, number: usize },
+}
+
+pub const CURRMOVE_DELAY: Duration = Duration::from_secs(1);
+
+pub type InfoCallback = Arc<dyn Fn(&SearchInfo) + Send + Sync>;
+
+pub fn nodes_per_second(nodes: u64, elapsed: Duration) -> u64 {
+    (nodes as u128 * 1_000_000 / elapsed.as_micros().max(1)) as u64
+}
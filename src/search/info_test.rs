@@ -0,0 +1,31 @@
+use super::info::{nodes_per_second, InfoScore};
+use super::score::mated_score;
+use crate::chess::PlayerColor;
+use std::time::Duration;
+
+#[test]
+fn test_info_scores() {
+    assert_eq!(InfoScore::from_score(-35), InfoScore::Centipawns(-35));
+    // Black is mated after white's second move.
+    let score = mated_score(PlayerColor::Black, 3);
+    assert_eq!(InfoScore::from_score(score), InfoScore::MateIn(2));
+    assert_eq!(
+        InfoScore::from_score(score).relative_to(PlayerColor::Black),
+        InfoScore::MateIn(-2)
+    );
+    assert_eq!(
+        InfoScore::Centipawns(12).relative_to(PlayerColor::White),
+        InfoScore::Centipawns(12)
+    );
+    assert_eq!(
+        InfoScore::Centipawns(12).relative_to(PlayerColor::Black),
+        InfoScore::Centipawns(-12)
+    );
+}
+
+#[test]
+fn test_nodes_per_second() {
+    assert_eq!(nodes_per_second(5000, Duration::from_millis(500)), 10_000);
+    // Searches too short to be timed do not divide by zero.
+    assert_eq!(nodes_per_second(42, Duration::ZERO), 42_000_000);
+}
@@ -0,0 +1,150 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+pub const MAX_SEARCH_DEPTH: u8 = 64;
+// Assumed number of moves left in the game when the clock does not say.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+// Kept in reserve on the clock to absorb communication and scheduling delays.
+const MOVE_OVERHEAD: Duration = Duration::from_millis(30);
+
+// When an iterative deepening search has to stop. Every limit is optional except the depth.
+#[derive(Clone)]
+pub struct SearchLimits {
+    pub max_depth: u8,
+    // No new iteration is started after this instant.
+    pub soft_deadline: Option<Instant>,
+    // The running iteration is aborted at this instant.
+    pub hard_deadline: Option<Instant>,
+    pub max_nodes: Option<u64>,
+    // Set from another thread to abort the search.
+    pub stop: Option<Arc<AtomicBool>>,
+    // Set when searching on the opponent's time. See Ponder.
+    pub ponder: Option<Arc<Ponder>>,
+}
+
+// Pondering: searching, on the opponent's time, the position after the move we expect them to
+// play. Until they play it (ponderhit), the deadlines of the search do not apply. They then
+// apply as if the search had started at the ponderhit, so that the time spent pondering comes
+// on top of the budget.
+pub struct Ponder {
+    started: Instant,
+    hit: OnceLock<Instant>,
+}
+
+impl Default for Ponder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ponder {
+    // To be created along with the deadlines it delays.
+    pub fn new() -> Self {
+        Ponder {
+            started: Instant::now(),
+            hit: OnceLock::new(),
+        }
+    }
+
+    // The opponent played the expected move: the search goes on, on our own time.
+    pub fn hit(&self) {
+        let _ = self.hit.set(Instant::now());
+    }
+
+    pub fn is_hit(&self) -> bool {
+        self.hit.get().is_some()
+    }
+
+    // How much later than set the deadlines are, or None while still pondering.
+    fn delay(&self) -> Option<Duration> {
+        self.hit.get().map(|hit| *hit - self.started)
+    }
+}
+
+impl Default for SearchLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SearchLimits {
+    // Searches until MAX_SEARCH_DEPTH or until stopped.
+    pub fn new() -> Self {
+        SearchLimits {
+            max_depth: MAX_SEARCH_DEPTH,
+            soft_deadline: None,
+            hard_deadline: None,
+            max_nodes: None,
+            stop: None,
+            ponder: None,
+        }
+    }
+
+    pub fn for_depth(depth: u8) -> Self {
+        SearchLimits {
+            max_depth: depth,
+            ..Self::new()
+        }
+    }
+
+    // Uses at most |move_time|, starting now.
+    pub fn for_move_time(move_time: Duration) -> Self {
+        let deadline = Instant::now() + move_time;
+        SearchLimits {
+            soft_deadline: Some(deadline),
+            hard_deadline: Some(deadline),
+            ..Self::new()
+        }
+    }
+
+    // Budgets time for one move of a timed game, starting now.
+    pub fn for_clock(remaining: Duration, increment: Duration, moves_to_go: Option<u32>) -> Self {
+        let (soft, hard) = allocate_time(remaining, increment, moves_to_go);
+        let now = Instant::now();
+        SearchLimits {
+            soft_deadline: Some(now + soft),
+            hard_deadline: Some(now + hard),
+            ..Self::new()
+        }
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stop
+            .as_ref()
+            .is_some_and(|stop| stop.load(Ordering::Relaxed))
+    }
+
+    pub fn is_past_soft_deadline(&self) -> bool {
+        self.is_past(self.soft_deadline)
+    }
+
+    pub fn is_past_hard_deadline(&self) -> bool {
+        self.is_past(self.hard_deadline)
+    }
+
+    fn is_past(&self, deadline: Option<Instant>) -> bool {
+        let delay = match &self.ponder {
+            Some(ponder) => match ponder.delay() {
+                Some(delay) => delay,
+                None => return false,
+            },
+            None => Duration::ZERO,
+        };
+        deadline.is_some_and(|deadline| Instant::now() >= deadline + delay)
+    }
+}
+
+// Returns the (soft, hard) time budget for the next move: the soft one is what we aim to spend,
+// the hard one is the most we are willing to spend to finish an iteration.
+pub fn allocate_time(
+    remaining: Duration,
+    increment: Duration,
+    moves_to_go: Option<u32>,
+) -> (Duration, Duration) {
+    let usable = remaining.saturating_sub(MOVE_OVERHEAD);
+    let moves_to_go = moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+    let soft = (usable / moves_to_go + increment * 3 / 4).min(usable);
+    let hard = (soft * 3).min(usable / 2).max(soft);
+    (soft, hard)
+}
@@ -0,0 +1,74 @@
+use super::limits::{allocate_time, Ponder, SearchLimits, MAX_SEARCH_DEPTH};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn test_allocate_time() {
+    let (soft, hard) = allocate_time(
+        Duration::from_millis(60_030),
+        Duration::from_millis(1_000),
+        None,
+    );
+    assert_eq!(soft, Duration::from_millis(2_750));
+    assert_eq!(hard, Duration::from_millis(8_250));
+
+    // The last move before the time control may use most of the clock, but not all of it.
+    let (soft, hard) = allocate_time(Duration::from_millis(1_030), Duration::ZERO, Some(1));
+    assert_eq!(soft, Duration::from_millis(1_000));
+    assert_eq!(hard, Duration::from_millis(1_000));
+
+    // Nearly flagging.
+    let (soft, hard) = allocate_time(Duration::from_millis(10), Duration::ZERO, None);
+    assert_eq!(soft, Duration::ZERO);
+    assert_eq!(hard, Duration::ZERO);
+}
+
+#[test]
+fn test_limits() {
+    let limits = SearchLimits::new();
+    assert_eq!(limits.max_depth, MAX_SEARCH_DEPTH);
+    assert!(!limits.is_past_soft_deadline());
+    assert!(!limits.is_past_hard_deadline());
+    assert!(!limits.is_stopped());
+
+    let limits = SearchLimits::for_move_time(Duration::ZERO);
+    assert!(limits.is_past_soft_deadline());
+    assert!(limits.is_past_hard_deadline());
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let limits = SearchLimits {
+        stop: Some(stop.clone()),
+        ..SearchLimits::for_depth(3)
+    };
+    assert_eq!(limits.max_depth, 3);
+    stop.store(true, Ordering::Relaxed);
+    assert!(limits.is_stopped());
+}
+
+#[test]
+fn test_ponder_delays_the_deadlines() {
+    let ponder = Arc::new(Ponder::new());
+    let limits = SearchLimits {
+        ponder: Some(ponder.clone()),
+        ..SearchLimits::for_move_time(Duration::ZERO)
+    };
+    assert!(!limits.is_past_soft_deadline());
+    assert!(!limits.is_past_hard_deadline());
+
+    ponder.hit();
+    assert!(ponder.is_hit());
+    assert!(limits.is_past_soft_deadline());
+    assert!(limits.is_past_hard_deadline());
+
+    // The time spent pondering does not count.
+    let ponder = Arc::new(Ponder::new());
+    let limits = SearchLimits {
+        ponder: Some(ponder.clone()),
+        ..SearchLimits::for_move_time(Duration::from_millis(50))
+    };
+    std::thread::sleep(Duration::from_millis(60));
+    ponder.hit();
+    assert!(!limits.is_past_hard_deadline());
+}
@@ -1 +1,12 @@
+pub mod info;
+pub mod limits;
+pub mod move_ordering;
+pub mod params;
+pub mod score;
+pub mod transposition_table;
 
+mod info_test;
+mod limits_test;
+mod move_ordering_test;
+mod score_test;
+mod transposition_table_test;
@@ -0,0 +1,187 @@
+// Order in which the search tries the moves of a position. Alpha-beta prunes the most when the
+// best move comes first, so the likeliest candidates go first: the best move of an earlier
+// search, then captures of valuable pieces by cheap ones, then quiet moves that caused cutoffs
+// at the same ply (killer moves), then the other quiet moves by how often they caused cutoffs
+// anywhere (history heuristic), and last the captures that lose material.
+use crate::chess::position::Position;
+use crate::chess::PlayerColor;
+use crate::evaluation::material_evaluator::PIECE_VALUES;
+use crate::move_gen::move_list::{Move, MoveList, MAX_MOVES};
+use crate::search::limits::MAX_SEARCH_DEPTH;
+
+const TT_MOVE_SCORE: i32 = 1 << 30;
+const CAPTURE_SCORE: i32 = 1 << 28;
+const KILLER_SCORE: i32 = 1 << 26;
+const LOSING_CAPTURE_SCORE: i32 = -(1 << 28);
+// History scores are halved once one reaches this, so that they stay below the killers.
+const MAX_HISTORY_SCORE: i32 = 1 << 24;
+pub const NUM_KILLERS: usize = 2;
+
+// Most valuable victim, least valuable attacker: captures are sorted by the captured piece,
+// then by the capturing one. Promotions count the promoted piece as a victim.
+pub fn mvv_lva(position: &Position, mv: &Move) -> i32 {
+    let victim = position
+        .captured_piece_type(mv)
+        .map_or(0, |typpe| typpe as i32 + 1);
+    let promoted = mv
+        .sp_move_type()
+        .promotion_piece_type()
+        .map_or(0, |typpe| typpe as i32);
+    (victim + promoted) * 8 - mv.piece_type() as i32
+}
+
+// Captures and promotions. Everything else is a quiet move.
+pub fn is_tactical(position: &Position, mv: &Move) -> bool {
+    position.captured_piece_type(mv).is_some() || mv.sp_move_type().promotion_piece_type().is_some()
+}
+
+// Captures and promotions that lose material once the exchange they start is played out.
+// Taking a piece worth at least the capturing one never does, which spares most exchange
+// evaluations.
+pub fn is_losing_capture(position: &Position, mv: &Move) -> bool {
+    let victim = position
+        .captured_piece_type(mv)
+        .map_or(0, |typpe| PIECE_VALUES[typpe as usize]);
+    let promotes = mv.sp_move_type().promotion_piece_type().is_some();
+    (promotes || victim < PIECE_VALUES[mv.piece_type() as usize]) && position.see(mv) < 0
+}
+
+// Quiet moves that caused a beta cutoff, by ply. Sibling positions often share refutations.
+pub struct KillerMoves {
+    killers: Vec<[Option<Move>; NUM_KILLERS]>,
+}
+
+impl KillerMoves {
+    pub fn new() -> Self {
+        KillerMoves {
+            killers: vec![[None; NUM_KILLERS]; MAX_SEARCH_DEPTH as usize + 1],
+        }
+    }
+
+    // The newest killer comes first, and the oldest one is forgotten.
+    pub fn store(&mut self, ply: u8, mv: Move) {
+        let killers = &mut self.killers[ply as usize];
+        if killers[0] != Some(mv) {
+            killers.rotate_right(1);
+            killers[0] = Some(mv);
+        }
+    }
+
+    // 0 for the newest killer of |ply|, 1 for the next one, None if |mv| is not a killer.
+    pub fn rank(&self, ply: u8, mv: &Move) -> Option<usize> {
+        self.killers[ply as usize]
+            .iter()
+            .position(|killer| *killer == Some(*mv))
+    }
+}
+
+impl Default for KillerMoves {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// How much each quiet move, by player, origin and destination, caused beta cutoffs. Deeper
+// cutoffs weigh more.
+pub struct HistoryTable {
+    scores: Vec<i32>,
+}
+
+impl HistoryTable {
+    pub fn new() -> Self {
+        HistoryTable {
+            scores: vec![0; 2 * 64 * 64],
+        }
+    }
+
+    fn index(color: PlayerColor, mv: &Move) -> usize {
+        (color as usize * 64 + mv.from() as usize) * 64 + mv.to() as usize
+    }
+
+    pub fn score(&self, color: PlayerColor, mv: &Move) -> i32 {
+        self.scores[Self::index(color, mv)]
+    }
+
+    pub fn reward(&mut self, color: PlayerColor, mv: &Move, depth: u8) {
+        let index = Self::index(color, mv);
+        self.scores[index] += depth as i32 * depth as i32;
+        if self.scores[index] >= MAX_HISTORY_SCORE {
+            self.scores.iter_mut().for_each(|score| *score /= 2);
+        }
+    }
+}
+
+impl Default for HistoryTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Hands out the moves of a position best first. Each call to next() looks for the best of the
+// remaining moves, which is cheaper than sorting them all when an early move causes a cutoff.
+pub struct MovePicker {
+    moves: MoveList,
+    scores: [i32; MAX_MOVES],
+    next: usize,
+}
+
+impl MovePicker {
+    pub fn new(
+        moves: MoveList,
+        position: &Position,
+        ply: u8,
+        tt_move: Option<Move>,
+        killers: &KillerMoves,
+        history: &HistoryTable,
+    ) -> Self {
+        let color = position.player_to_move();
+        let mut scores = [0; MAX_MOVES];
+        for (score, mv) in scores.iter_mut().zip(moves.iter()) {
+            *score = if Some(*mv) == tt_move {
+                TT_MOVE_SCORE
+            } else if is_tactical(position, mv) {
+                match is_losing_capture(position, mv) {
+                    true => LOSING_CAPTURE_SCORE + mvv_lva(position, mv),
+                    false => CAPTURE_SCORE + mvv_lva(position, mv),
+                }
+            } else if let Some(rank) = killers.rank(ply, mv) {
+                KILLER_SCORE - rank as i32
+            } else {
+                history.score(color, mv)
+            };
+        }
+        MovePicker {
+            moves,
+            scores,
+            next: 0,
+        }
+    }
+
+    // Hands out the moves in the order they were generated.
+    pub fn unordered(moves: MoveList) -> Self {
+        MovePicker {
+            moves,
+            scores: [0; MAX_MOVES],
+            next: 0,
+        }
+    }
+}
+
+impl Iterator for MovePicker {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        if self.next >= self.moves.len() {
+            return None;
+        }
+        // Reversed, so that the first of equally scored moves is picked.
+        let best = (self.next..self.moves.len())
+            .rev()
+            .max_by_key(|i| self.scores[*i])
+            .unwrap();
+        self.moves.swap(self.next, best);
+        self.scores.swap(self.next, best);
+        self.next += 1;
+        Some(self.moves[self.next - 1])
+    }
+}
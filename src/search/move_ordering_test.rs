@@ -0,0 +1,91 @@
+use super::move_ordering::{mvv_lva, HistoryTable, KillerMoves, MovePicker};
+use crate::chess::position::Position;
+use crate::chess::PlayerColor;
+use crate::move_gen::move_list::Move;
+
+// Kiwipete: eight captures for white, by and of various pieces.
+const KIWIPETE_FEN: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn find(position: &Position, coordinates: &str) -> Move {
+    position.find_legal_move(coordinates).unwrap()
+}
+
+fn picked(
+    position: &Position,
+    tt_move: Option<Move>,
+    killers: &KillerMoves,
+    history: &HistoryTable,
+) -> Vec<String> {
+    MovePicker::new(
+        position.legal_continuations(),
+        position,
+        0,
+        tt_move,
+        killers,
+        history,
+    )
+    .map(|mv| mv.to_coordinates())
+    .collect()
+}
+
+#[test]
+fn test_mvv_lva() {
+    let position = Position::from_fen(KIWIPETE_FEN).unwrap();
+    let score = |coordinates| mvv_lva(&position, &find(&position, coordinates));
+    // Bishop takes bishop, queen takes knight, then pawns taken by a pawn, a knight, a queen.
+    assert!(score("e2a6") > score("f3f6"));
+    assert!(score("f3f6") > score("g2h3"));
+    assert!(score("g2h3") > score("e5g6"));
+    assert!(score("e5g6") > score("f3h3"));
+    assert!(score("f3h3") > score("a2a3"));
+}
+
+#[test]
+fn test_move_picker_order() {
+    let position = Position::from_fen(KIWIPETE_FEN).unwrap();
+    let mut killers = KillerMoves::new();
+    let mut history = HistoryTable::new();
+    let order = picked(&position, None, &killers, &history);
+    assert_eq!(order.len(), 48);
+    // The queen would be lost to the defenders of f6 and h3 (the rook on h8), the knights to
+    // those of the pawns. Those captures come last, after the quiet moves.
+    let mut pawns_taken_by_pawns = order[1..3].to_vec();
+    pawns_taken_by_pawns.sort();
+    let mut pawns_taken_by_knights = order[44..47].to_vec();
+    pawns_taken_by_knights.sort();
+    assert_eq!(order[0], "e2a6");
+    assert_eq!(pawns_taken_by_pawns, vec!["d5e6", "g2h3"]);
+    assert_eq!(order[43], "f3f6");
+    assert_eq!(pawns_taken_by_knights, vec!["e5d7", "e5f7", "e5g6"]);
+    assert_eq!(order[47], "f3h3");
+
+    // The move of the transposition table comes first, then captures, killers and history.
+    killers.store(0, find(&position, "a2a3"));
+    history.reward(PlayerColor::White, &find(&position, "b2b3"), 3);
+    let order = picked(&position, Some(find(&position, "e1g1")), &killers, &history);
+    assert_eq!(order[0], "e1g1");
+    assert_eq!(order[1], "e2a6");
+    assert_eq!(order[4], "a2a3");
+    assert_eq!(order[5], "b2b3");
+}
+
+#[test]
+fn test_killer_moves() {
+    let position = Position::new();
+    let (a3, b3, c3) = (
+        find(&position, "a2a3"),
+        find(&position, "b2b3"),
+        find(&position, "c2c3"),
+    );
+    let mut killers = KillerMoves::new();
+    killers.store(3, a3);
+    killers.store(3, a3);
+    assert_eq!(killers.rank(3, &a3), Some(0));
+    killers.store(3, b3);
+    assert_eq!(killers.rank(3, &b3), Some(0));
+    assert_eq!(killers.rank(3, &a3), Some(1));
+    killers.store(3, c3);
+    assert_eq!(killers.rank(3, &a3), None);
+    // Killers belong to their ply.
+    assert_eq!(killers.rank(2, &c3), None);
+}
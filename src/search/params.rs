@@ -0,0 +1,236 @@
+// Tunable parameters of the search. Every technique has its own switch, so that its effect can
+// be measured on its own.
+#[derive(Clone, Copy, Debug)]
+pub struct SearchParams {
+    // Moves are searched best first, as guessed by the MovePicker, so that cutoffs come early.
+    // Otherwise they are searched in the order they are generated.
+    pub move_ordering: bool,
+    // Every move but the first is searched with a null window, which only tells whether it
+    // beats the best move so far. The few that do are searched again with the full window.
+    pub principal_variation_search: bool,
+    pub aspiration: AspirationParams,
+    // Selective search: the techniques below skip or shorten the search of the moves that are
+    // unlikely to matter, or lengthen that of forcing ones. Unlike the two above, they change
+    // the score. The pruning ones only apply to nodes searched with a null window.
+    pub null_move: NullMoveParams,
+    pub late_move_reductions: LateMoveReductionParams,
+    pub futility: FutilityParams,
+    pub reverse_futility: ReverseFutilityParams,
+    pub razoring: RazoringParams,
+    pub check_extensions: CheckExtensionParams,
+}
+
+impl Default for SearchParams {
+    fn default() -> Self {
+        SearchParams {
+            move_ordering: true,
+            principal_variation_search: true,
+            aspiration: AspirationParams::default(),
+            null_move: NullMoveParams::default(),
+            late_move_reductions: LateMoveReductionParams::default(),
+            futility: FutilityParams::default(),
+            reverse_futility: ReverseFutilityParams::default(),
+            razoring: RazoringParams::default(),
+            check_extensions: CheckExtensionParams::default(),
+        }
+    }
+}
+
+impl SearchParams {
+    // Full-window alpha-beta, which the other techniques must agree with.
+    pub fn plain_alpha_beta() -> Self {
+        SearchParams {
+            principal_variation_search: false,
+            aspiration: AspirationParams {
+                enabled: false,
+                ..AspirationParams::default()
+            },
+            ..Self::full_width()
+        }
+    }
+
+    // Searches every move to the full depth: the default parameters without selective search.
+    pub fn full_width() -> Self {
+        let default = Self::default();
+        SearchParams {
+            null_move: NullMoveParams {
+                enabled: false,
+                ..default.null_move
+            },
+            late_move_reductions: LateMoveReductionParams {
+                enabled: false,
+                ..default.late_move_reductions
+            },
+            futility: FutilityParams {
+                enabled: false,
+                ..default.futility
+            },
+            reverse_futility: ReverseFutilityParams {
+                enabled: false,
+                ..default.reverse_futility
+            },
+            razoring: RazoringParams {
+                enabled: false,
+                ..default.razoring
+            },
+            check_extensions: CheckExtensionParams {
+                enabled: false,
+                ..default.check_extensions
+            },
+            ..default
+        }
+    }
+}
+
+// Each iteration of the root starts with a window around the score of the previous one, which
+// cuts off more than a full window. When the score falls outside, the window is widened on
+// that side and the iteration searched again.
+#[derive(Clone, Copy, Debug)]
+pub struct AspirationParams {
+    pub enabled: bool,
+    // Shallower iterations use the full window: their scores vary too much.
+    pub min_depth: u8,
+    // Distance between the previous score and each bound of the first window, in centipawns.
+    // It doubles whenever the score falls outside.
+    pub initial_delta: i32,
+}
+
+impl Default for AspirationParams {
+    fn default() -> Self {
+        AspirationParams {
+            enabled: true,
+            min_depth: 4,
+            initial_delta: 50,
+        }
+    }
+}
+
+// When passing the turn still leaves the player to move at or above beta, a real move would
+// too, so the node is cut off after a shallow search of the position after the pass. Not
+// tried in check, without pieces other than pawns (where zugzwang, being forced to make a bad
+// move, is common) or right after another pass.
+#[derive(Clone, Copy, Debug)]
+pub struct NullMoveParams {
+    pub enabled: bool,
+    pub min_depth: u8,
+    // The position after the pass is searched this many plies shallower than the moves,
+    // plus one more every |depth_divisor| plies of remaining depth.
+    pub reduction: u8,
+    pub depth_divisor: u8,
+}
+
+impl Default for NullMoveParams {
+    fn default() -> Self {
+        NullMoveParams {
+            enabled: true,
+            min_depth: 3,
+            reduction: 2,
+            depth_divisor: 4,
+        }
+    }
+}
+
+// Quiet moves that come late in the move ordering rarely turn out best: they are searched
+// shallower first, and again at the full depth if they beat alpha anyway.
+#[derive(Clone, Copy, Debug)]
+pub struct LateMoveReductionParams {
+    pub enabled: bool,
+    pub min_depth: u8,
+    // Moves before this index in the move ordering are never reduced.
+    pub min_move_index: usize,
+    pub reduction: u8,
+}
+
+impl Default for LateMoveReductionParams {
+    fn default() -> Self {
+        LateMoveReductionParams {
+            enabled: true,
+            min_depth: 3,
+            min_move_index: 3,
+            reduction: 1,
+        }
+    }
+}
+
+// Close to the horizon, when the static evaluation is so far below alpha that a quiet move is
+// not going to make up for it, quiet moves are skipped. Moves giving check are kept.
+#[derive(Clone, Copy, Debug)]
+pub struct FutilityParams {
+    pub enabled: bool,
+    pub max_depth: u8,
+    // How much a quiet move may gain, per ply of remaining depth, in centipawns.
+    pub margin_per_depth: i32,
+}
+
+impl Default for FutilityParams {
+    fn default() -> Self {
+        FutilityParams {
+            enabled: true,
+            max_depth: 2,
+            margin_per_depth: 150,
+        }
+    }
+}
+
+// The opposite of futility pruning: close to the horizon, when the static evaluation is so far
+// above beta that the opponent is not going to make up for it, the node is cut off without
+// searching.
+#[derive(Clone, Copy, Debug)]
+pub struct ReverseFutilityParams {
+    pub enabled: bool,
+    pub max_depth: u8,
+    // How much the opponent may gain, per ply of remaining depth, in centipawns.
+    pub margin_per_depth: i32,
+}
+
+impl Default for ReverseFutilityParams {
+    fn default() -> Self {
+        ReverseFutilityParams {
+            enabled: true,
+            max_depth: 3,
+            margin_per_depth: 120,
+        }
+    }
+}
+
+// Close to the horizon, when the static evaluation is far below alpha, the node drops into
+// the quiescence search. If captures and checks do not bring the score back above alpha, the
+// node fails low without searching quiet moves.
+#[derive(Clone, Copy, Debug)]
+pub struct RazoringParams {
+    pub enabled: bool,
+    pub max_depth: u8,
+    // Distance below alpha, per ply of remaining depth, in centipawns.
+    pub margin_per_depth: i32,
+}
+
+impl Default for RazoringParams {
+    fn default() -> Self {
+        RazoringParams {
+            enabled: true,
+            max_depth: 2,
+            margin_per_depth: 250,
+        }
+    }
+}
+
+// More plies of check extension would deepen the search with every check of a series of
+// checks.
+pub const MAX_CHECK_EXTENSION: u8 = 1;
+
+// Moves that give check are searched deeper, so that forcing lines are not cut short by the
+// horizon.
+#[derive(Clone, Copy, Debug)]
+pub struct CheckExtensionParams {
+    pub enabled: bool,
+    pub plies: u8,
+}
+
+impl Default for CheckExtensionParams {
+    fn default() -> Self {
+        CheckExtensionParams {
+            enabled: true,
+            plies: 1,
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use crate::chess::PlayerColor;
+use crate::search::limits::MAX_SEARCH_DEPTH;
+
+// Score of being mated right now. Being mated in N plies scores -(MATE_SCORE - N), so that
+// shorter mates are preferred. Any score beyond MATE_THRESHOLD is a mate score.
+pub const MATE_SCORE: i32 = 1_000_000;
+pub const MATE_THRESHOLD: i32 = MATE_SCORE - MAX_SEARCH_DEPTH as i32 - 1;
+
+// Bound on every score, mates included. Unlike i32::MIN, it can be negated.
+pub const INFINITE_SCORE: i32 = MATE_SCORE + 1;
+
+// Score, from the point of view of the player to move, of being checkmated |ply| plies away
+// from the root.
+pub fn mated_in(ply: u8) -> i32 {
+    -(MATE_SCORE - ply as i32)
+}
+
+// Score, from white's point of view, of |moving_player| being checkmated |ply| plies away
+// from the root.
+pub fn mated_score(moving_player: PlayerColor, ply: u8) -> i32 {
+    relative_to(moving_player, mated_in(ply))
+}
+
+// Turns a score from white's point of view into one from |color|'s point of view, and back.
+pub fn relative_to(color: PlayerColor, score: i32) -> i32 {
+    match color {
+        PlayerColor::White => score,
+        PlayerColor::Black => -score,
+    }
+}
+
+pub fn is_mate_score(score: i32) -> bool {
+    score.abs() > MATE_THRESHOLD
+}
+
+// Number of moves until checkmate for a mate score from white's point of view: positive when
+// white mates, negative when black does.
+pub fn mate_in_moves(score: i32) -> Option<i32> {
+    if !is_mate_score(score) {
+        return None;
+    }
+    let plies = MATE_SCORE - score.abs();
+    let moves = (plies + 1) / 2;
+    Some(if score > 0 { moves } else { -moves })
+}
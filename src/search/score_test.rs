@@ -0,0 +1,31 @@
+use super::score::{
+    is_mate_score, mate_in_moves, mated_in, mated_score, relative_to, INFINITE_SCORE, MATE_SCORE,
+};
+use crate::chess::PlayerColor;
+
+#[test]
+fn test_mate_in_moves() {
+    // White to move mates with its first move.
+    assert_eq!(mate_in_moves(mated_score(PlayerColor::Black, 1)), Some(1));
+    // White to move is mated after its reply.
+    assert_eq!(mate_in_moves(mated_score(PlayerColor::White, 2)), Some(-1));
+    assert_eq!(mate_in_moves(mated_score(PlayerColor::Black, 5)), Some(3));
+    assert_eq!(mate_in_moves(-(MATE_SCORE - 4)), Some(-2));
+    // Already mated.
+    assert_eq!(mate_in_moves(mated_score(PlayerColor::White, 0)), Some(0));
+
+    assert_eq!(mate_in_moves(900), None);
+    assert!(!is_mate_score(-900));
+    assert!(mated_score(PlayerColor::Black, 3) > mated_score(PlayerColor::Black, 5));
+}
+
+#[test]
+fn test_relative_scores() {
+    assert_eq!(relative_to(PlayerColor::White, 120), 120);
+    assert_eq!(relative_to(PlayerColor::Black, 120), -120);
+    assert_eq!(
+        relative_to(PlayerColor::Black, mated_in(3)),
+        mated_score(PlayerColor::Black, 3)
+    );
+    assert!(-INFINITE_SCORE < mated_in(0) && -mated_in(0) < INFINITE_SCORE);
+}
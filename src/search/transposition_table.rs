@@ -0,0 +1,204 @@
+use crate::move_gen::move_list::Move;
+use crate::search::score::MATE_THRESHOLD;
+
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+pub const DEFAULT_TT_SIZE_MB: usize = 16;
+pub const MAX_TT_SIZE_MB: usize = 4096;
+// Slots read to estimate how full the table is. Sampling only a thousand would read a table
+// filled to a few permille, as after a shallow search, as empty.
+const HASHFULL_SAMPLE: usize = 1 << 16;
+
+// How the stored score relates to the true score of the position.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Bound {
+    Exact,
+    // The search failed high: the true score is at least the stored one.
+    Lower,
+    // The search failed low: the true score is at most the stored one.
+    Upper,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TTEntry {
+    pub key: u64,
+    pub depth: u8,
+    pub bound: Bound,
+    // Mate scores are relative to this position rather than to the root. Use
+    // score_from_tt() to read them back.
+    pub score: i32,
+    pub best_move: Option<Move>,
+    // Generation of the search that stored the entry.
+    pub age: u8,
+}
+
+// Mate scores are stored as a distance from the stored position, since the same position can
+// be reached at different plies.
+pub fn score_to_tt(score: i32, ply: u8) -> i32 {
+    if score > MATE_THRESHOLD {
+        score + ply as i32
+    } else if score < -MATE_THRESHOLD {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+pub fn score_from_tt(score: i32, ply: u8) -> i32 {
+    if score > MATE_THRESHOLD {
+        score - ply as i32
+    } else if score < -MATE_THRESHOLD {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+// Entries are packed in 64 bits: the best move in bits 0-18 (0 when there is none, a1a1 not
+// being a move), the depth in bits 19-26, the bound in bits 27-28 (0 for an empty slot), the
+// age in bits 29-36 and the score, signed, in bits 37-63.
+const MOVE_BITS: u64 = 0x7ffff;
+const DEPTH_SHIFT: u32 = 19;
+const BOUND_SHIFT: u32 = 27;
+const AGE_SHIFT: u32 = 29;
+const SCORE_SHIFT: u32 = 37;
+
+fn pack(entry: &TTEntry) -> u64 {
+    let bound = match entry.bound {
+        Bound::Exact => 1,
+        Bound::Lower => 2,
+        Bound::Upper => 3,
+    };
+    entry.best_move.map_or(0, |mv| mv.bits() as u64)
+        | (entry.depth as u64) << DEPTH_SHIFT
+        | bound << BOUND_SHIFT
+        | (entry.age as u64) << AGE_SHIFT
+        | (entry.score as i64 as u64) << SCORE_SHIFT
+}
+
+fn unpack(key: u64, data: u64) -> Option<TTEntry> {
+    let bound = match data >> BOUND_SHIFT & 0x3 {
+        1 => Bound::Exact,
+        2 => Bound::Lower,
+        3 => Bound::Upper,
+        _ => return None,
+    };
+    Some(TTEntry {
+        key,
+        depth: (data >> DEPTH_SHIFT) as u8,
+        bound,
+        score: (data as i64 >> SCORE_SHIFT) as i32,
+        best_move: Some(data & MOVE_BITS)
+            .filter(|bits| *bits != 0)
+            .map(|bits| Move::from_bits(bits as u32)),
+        age: (data >> AGE_SHIFT) as u8,
+    })
+}
+
+// The key is stored xored with the data. A slot written by two threads at once can end up
+// with the key of one entry and the data of the other: the key then no longer matches, and
+// the slot reads as another position's.
+#[derive(Default)]
+struct Slot {
+    checked_key: AtomicU64,
+    data: AtomicU64,
+}
+
+// Fixed-size hash table of search results, indexed by the zobrist hash of the position.
+// Each slot holds a single entry; see store() for the replacement scheme. The search threads
+// share it without locking: see Slot.
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+    age: AtomicU8,
+}
+
+impl TranspositionTable {
+    // Size of one entry in the table.
+    pub const ENTRY_SIZE: usize = size_of::<Slot>();
+
+    pub fn new(size_mb: usize) -> Self {
+        let mut result = TranspositionTable {
+            slots: vec![],
+            age: AtomicU8::new(0),
+        };
+        result.resize(size_mb);
+        result
+    }
+
+    // Discards every entry. The number of slots is the largest power of two that fits in
+    // |size_mb| megabytes.
+    pub fn resize(&mut self, size_mb: usize) {
+        let max_entries = (size_mb.clamp(1, MAX_TT_SIZE_MB) << 20) / Self::ENTRY_SIZE;
+        let num_entries = 1 << (usize::BITS - 1 - max_entries.leading_zeros());
+        self.slots = (0..num_entries).map(|_| Slot::default()).collect();
+    }
+
+    pub fn clear(&self) {
+        for slot in self.slots.iter() {
+            slot.checked_key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+        self.age.store(0, Ordering::Relaxed);
+    }
+
+    pub fn num_entries(&self) -> usize {
+        self.slots.len()
+    }
+
+    // To be called before every search, so that entries from previous searches get replaced
+    // first.
+    pub fn new_search(&self) {
+        self.age.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // How full the table is, in permille, counting only the entries of the current search.
+    // Estimated from the first slots, for the UCI "hashfull" info.
+    pub fn hashfull(&self) -> u16 {
+        let age = self.age.load(Ordering::Relaxed) as u64;
+        let sample = &self.slots[..self.slots.len().min(HASHFULL_SAMPLE)];
+        let used = sample
+            .iter()
+            .map(|slot| slot.data.load(Ordering::Relaxed))
+            .filter(|data| data >> BOUND_SHIFT & 0x3 != 0 && (data >> AGE_SHIFT) & 0xff == age)
+            .count();
+        (used * 1000 / sample.len()) as u16
+    }
+
+    fn slot(&self, key: u64) -> &Slot {
+        &self.slots[key as usize & (self.slots.len() - 1)]
+    }
+
+    pub fn probe(&self, key: u64) -> Option<TTEntry> {
+        let slot = self.slot(key);
+        let data = slot.data.load(Ordering::Relaxed);
+        if slot.checked_key.load(Ordering::Relaxed) ^ data != key {
+            return None;
+        }
+        unpack(key, data)
+    }
+
+    // An existing entry is only replaced by a shallower search of another position if it was
+    // stored by a previous search. A best move is never forgotten in favor of no move.
+    pub fn store(&self, key: u64, depth: u8, bound: Bound, score: i32, best_move: Option<Move>) {
+        let age = self.age.load(Ordering::Relaxed);
+        let slot = self.slot(key);
+        let old_data = slot.data.load(Ordering::Relaxed);
+        let old_key = slot.checked_key.load(Ordering::Relaxed) ^ old_data;
+        let best_move = match unpack(old_key, old_data) {
+            Some(old) if old.key == key => best_move.or(old.best_move),
+            Some(old) if old.age == age && old.depth > depth => return,
+            _ => best_move,
+        };
+        let data = pack(&TTEntry {
+            key,
+            depth,
+            bound,
+            score,
+            best_move,
+            age,
+        });
+        slot.data.store(data, Ordering::Relaxed);
+        slot.checked_key.store(key ^ data, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,161 @@
+use super::transposition_table::{score_from_tt, score_to_tt, Bound, TranspositionTable};
+use crate::chess::bitboard::SpecialMoveType;
+use crate::chess::PieceType;
+use crate::move_gen::move_list::Move;
+use crate::search::score::MATE_SCORE;
+
+fn mv(from: u8, to: u8) -> Option<Move> {
+    Some(Move::new(
+        from,
+        to,
+        SpecialMoveType::RegularMove,
+        PieceType::Knight,
+    ))
+}
+
+#[test]
+fn test_size() {
+    let tt = TranspositionTable::new(1);
+    assert!(tt.num_entries().is_power_of_two());
+    assert!(tt.num_entries() * TranspositionTable::ENTRY_SIZE <= 1 << 20);
+    assert!(TranspositionTable::new(2).num_entries() == 2 * tt.num_entries());
+}
+
+#[test]
+fn test_store_and_probe() {
+    let tt = TranspositionTable::new(1);
+    let key = 0xdead_beef_1234_5678;
+    assert_eq!(tt.probe(key), None);
+
+    tt.store(key, 3, Bound::Exact, 42, mv(12, 28));
+    let entry = tt.probe(key).unwrap();
+    assert_eq!(
+        (entry.depth, entry.bound, entry.score),
+        (3, Bound::Exact, 42)
+    );
+    assert_eq!(entry.best_move, mv(12, 28));
+
+    // Same slot, different key.
+    assert_eq!(tt.probe(key ^ (1 << 63)), None);
+
+    // A search of the same position without a best move keeps the old one.
+    tt.store(key, 1, Bound::Upper, -7, None);
+    let entry = tt.probe(key).unwrap();
+    assert_eq!(
+        (entry.depth, entry.bound, entry.score),
+        (1, Bound::Upper, -7)
+    );
+    assert_eq!(entry.best_move, mv(12, 28));
+
+    tt.clear();
+    assert_eq!(tt.probe(key), None);
+}
+
+#[test]
+fn test_replacement_scheme() {
+    let tt = TranspositionTable::new(1);
+    let key = 17;
+    let other_key = key + tt.num_entries() as u64;
+    tt.store(key, 5, Bound::Exact, 1, mv(1, 2));
+
+    // A shallower entry of the same search does not evict a deeper one.
+    tt.store(other_key, 4, Bound::Exact, 2, mv(3, 4));
+    assert_eq!(tt.probe(key).unwrap().score, 1);
+    assert_eq!(tt.probe(other_key), None);
+
+    // An entry as deep does.
+    tt.store(other_key, 5, Bound::Lower, 2, mv(3, 4));
+    assert_eq!(tt.probe(key), None);
+    assert_eq!(tt.probe(other_key).unwrap().score, 2);
+
+    // Entries of previous searches are always replaced.
+    tt.new_search();
+    tt.store(key, 1, Bound::Exact, 3, None);
+    assert_eq!(tt.probe(key).unwrap().score, 3);
+    assert_eq!(tt.probe(other_key), None);
+}
+
+#[test]
+fn test_mate_score_adjustment() {
+    // Mated in 5 plies from the root, stored at ply 2: mated in 3 plies from there.
+    assert_eq!(score_to_tt(-(MATE_SCORE - 5), 2), -(MATE_SCORE - 3));
+    assert_eq!(score_from_tt(-(MATE_SCORE - 3), 2), -(MATE_SCORE - 5));
+    // Read back at ply 4, it is a mate in 7 plies from the root.
+    assert_eq!(
+        score_from_tt(score_to_tt(MATE_SCORE - 5, 2), 4),
+        MATE_SCORE - 7
+    );
+
+    assert_eq!(score_to_tt(150, 9), 150);
+    assert_eq!(score_from_tt(-150, 9), -150);
+}
+
+#[test]
+fn test_entries_are_packed_without_loss() {
+    let tt = TranspositionTable::new(1);
+    let promotion = Some(Move::new(
+        52,
+        60,
+        SpecialMoveType::PromotionToQueen,
+        PieceType::Pawn,
+    ));
+    for (key, depth, bound, score, best_move) in [
+        (1, 0, Bound::Exact, 0, None),
+        (2, 255, Bound::Lower, MATE_SCORE, promotion),
+        (3, 17, Bound::Upper, -MATE_SCORE, mv(63, 0)),
+        (4, 1, Bound::Exact, -1, mv(0, 63)),
+    ] {
+        tt.store(key, depth, bound, score, best_move);
+        let entry = tt.probe(key).unwrap();
+        assert_eq!(
+            (
+                entry.key,
+                entry.depth,
+                entry.bound,
+                entry.score,
+                entry.best_move
+            ),
+            (key, depth, bound, score, best_move)
+        );
+    }
+}
+
+#[test]
+fn test_concurrent_stores_never_mix_entries() {
+    let tt = TranspositionTable::new(1);
+    let num_entries = tt.num_entries() as u64;
+    // All the threads write to the same few slots, each entry with a score tied to its key.
+    std::thread::scope(|scope| {
+        for thread in 0..4 {
+            let tt = &tt;
+            scope.spawn(move || {
+                for i in 0..20_000u64 {
+                    let key = (i % 8) + (thread + i % 3) * num_entries;
+                    tt.store(key, (i % 5) as u8, Bound::Exact, key as i32, None);
+                    if let Some(entry) = tt.probe(key) {
+                        assert_eq!(entry.score, key as i32);
+                    }
+                }
+            });
+        }
+    });
+}
+
+#[test]
+fn test_hashfull() {
+    let tt = TranspositionTable::new(1);
+    assert_eq!(tt.hashfull(), 0);
+    let eighth = tt.num_entries() as u64 / 8;
+    for key in 0..eighth {
+        tt.store(key, 1, Bound::Exact, 0, None);
+    }
+    assert_eq!(tt.hashfull(), 125);
+    // Entries of previous searches do not count.
+    tt.new_search();
+    assert_eq!(tt.hashfull(), 0);
+    // A permille of the table, as few entries as a shallow search stores, still shows.
+    for key in 0..tt.num_entries() as u64 / 1000 + 1 {
+        tt.store(key, 1, Bound::Exact, 0, None);
+    }
+    assert_eq!(tt.hashfull(), 1);
+}
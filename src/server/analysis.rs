@@ -0,0 +1,221 @@
+use crate::chess::history::PositionHistory;
+use crate::evaluation::search_evaluator::MinimaxSearchEvaluator;
+use crate::evaluation::PositionEvaluationPipeline;
+use crate::search::info::SearchInfo;
+use crate::search::limits::{SearchLimits, MAX_SEARCH_DEPTH};
+use crate::{chess::position::Position, AnalysisStatus, UciRequest};
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+// Sessions that run at once. Starting one more stops the oldest.
+pub const MAX_SESSIONS: usize = 4;
+// A session that has not been polled for this long is stopped: nobody is reading its lines.
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+// Infinite analysis of one position, searched in the background until stopped.
+struct AnalysisSession {
+    stop: Arc<AtomicBool>,
+    status: Arc<Mutex<AnalysisStatus>>,
+    // When the session was started or last polled.
+    last_poll: Arc<Mutex<Instant>>,
+    search_thread: JoinHandle<()>,
+}
+
+impl AnalysisSession {
+    // Stops the search and waits for it. Returns the last lines, or an error when the search
+    // died.
+    fn finish(self) -> Result<AnalysisStatus, String> {
+        self.stop.store(true, Ordering::Relaxed);
+        if self.search_thread.join().is_err() {
+            return Err("analysis failed".to_string());
+        }
+        let status = self.status.lock().unwrap().clone();
+        Ok(status)
+    }
+}
+
+// The analysis sessions of the server, by id. Unlike the requests of the `engine` handler,
+// which answer once their search is done, sessions answer right away and are polled for the
+// lines found so far.
+pub struct AnalysisSessions {
+    max_sessions: usize,
+    idle_timeout: Duration,
+    next_id: AtomicU64,
+    sessions: Mutex<HashMap<u64, AnalysisSession>>,
+}
+
+impl Default for AnalysisSessions {
+    fn default() -> Self {
+        Self::new(MAX_SESSIONS, IDLE_TIMEOUT)
+    }
+}
+
+impl AnalysisSessions {
+    pub fn new(max_sessions: usize, idle_timeout: Duration) -> Self {
+        AnalysisSessions {
+            max_sessions: max_sessions.max(1),
+            idle_timeout,
+            next_id: AtomicU64::new(0),
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Starts analyzing the position of |uci_req|, with as many lines as |uci_req.multi_pv|
+    // asks. The timeout of the request is ignored: the session runs until stopped, until it
+    // is left idle, or until the search has nothing left to find. Idle sessions, then the
+    // oldest ones, are stopped and forgotten to make room for it.
+    pub fn start(&self, uci_req: &UciRequest) -> AnalysisStatus {
+        let session_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let position = Position::from_uci(uci_req);
+        let num_lines = super::num_lines(uci_req);
+        // The sessions share the cores, so that together they never search with more threads
+        // than a single search would.
+        let threads = (super::search_threads() / self.max_sessions).max(1);
+        let idle_timeout = self.idle_timeout;
+        let stop = Arc::new(AtomicBool::new(false));
+        let status = Arc::new(Mutex::new(AnalysisStatus {
+            session_id,
+            running: true,
+            lines: vec![],
+        }));
+        let last_poll = Arc::new(Mutex::new(Instant::now()));
+        let search_thread = {
+            let (stop, status, last_poll) = (stop.clone(), status.clone(), last_poll.clone());
+            thread::spawn(move || {
+                let idle = IdleTimeout {
+                    last_poll: &last_poll,
+                    timeout: idle_timeout,
+                };
+                analyze(&position, num_lines, threads, &idle, &stop, &status)
+            })
+        };
+        let result = status.lock().unwrap().clone();
+        let evicted = {
+            let mut sessions = self.sessions.lock().unwrap();
+            let is_idle = |session: &AnalysisSession| {
+                session.last_poll.lock().unwrap().elapsed() >= idle_timeout
+            };
+            let mut evicted_ids = sessions
+                .iter()
+                .filter(|(_, session)| is_idle(session))
+                .map(|(id, _)| *id)
+                .collect::<Vec<u64>>();
+            let mut active_ids = sessions
+                .keys()
+                .filter(|id| !evicted_ids.contains(id))
+                .copied()
+                .collect::<Vec<u64>>();
+            active_ids.sort();
+            let excess = (active_ids.len() + 1).saturating_sub(self.max_sessions);
+            evicted_ids.extend(&active_ids[..excess]);
+            let evicted = evicted_ids
+                .iter()
+                .filter_map(|id| sessions.remove(id))
+                .collect::<Vec<AnalysisSession>>();
+            sessions.insert(
+                session_id,
+                AnalysisSession {
+                    stop,
+                    status,
+                    last_poll,
+                    search_thread,
+                },
+            );
+            evicted
+        };
+        // Nobody is waiting for their lines.
+        for session in evicted {
+            let _ = session.finish();
+        }
+        result
+    }
+
+    // The lines found so far, or None for an unknown session.
+    pub fn poll(&self, session_id: u64) -> Option<AnalysisStatus> {
+        let sessions = self.sessions.lock().unwrap();
+        let session = sessions.get(&session_id)?;
+        *session.last_poll.lock().unwrap() = Instant::now();
+        let status = session.status.lock().unwrap().clone();
+        Some(status)
+    }
+
+    // Stops the session and forgets it. Returns its last lines, an error when its search
+    // died, or None for an unknown session.
+    pub fn stop(&self, session_id: u64) -> Option<Result<AnalysisStatus, String>> {
+        let session = self.sessions.lock().unwrap().remove(&session_id)?;
+        Some(session.finish())
+    }
+}
+
+// Stops a search once its session has not been polled for |timeout|.
+struct IdleTimeout<'a> {
+    last_poll: &'a Mutex<Instant>,
+    timeout: Duration,
+}
+
+impl IdleTimeout<'_> {
+    // Raises |stop| once the session is idle, unless |searching| is lowered first. The thread
+    // has to be unparked when it is.
+    fn watch(&self, stop: &AtomicBool, searching: &AtomicBool) {
+        while searching.load(Ordering::Relaxed) {
+            let idle = self.last_poll.lock().unwrap().elapsed();
+            if idle >= self.timeout {
+                stop.store(true, Ordering::Relaxed);
+                return;
+            }
+            thread::park_timeout(self.timeout - idle);
+        }
+    }
+}
+
+// Searches with |threads| threads until |stop| is raised, until the session is |idle|, or
+// until the search has nothing left to find. The lines of each completed iteration are
+// published in |status|, from the progress reports of the search.
+fn analyze(
+    position: &Position,
+    num_lines: usize,
+    threads: usize,
+    idle: &IdleTimeout,
+    stop: &Arc<AtomicBool>,
+    status: &Arc<Mutex<AnalysisStatus>>,
+) {
+    // The lines of an iteration are reported one by one, best first.
+    let num_lines = num_lines.min(position.legal_continuations().len()).max(1);
+    let iteration_lines = Mutex::new(vec![]);
+    let published = status.clone();
+    let evaluator = MinimaxSearchEvaluator::new(
+        Box::new(PositionEvaluationPipeline::new_default()),
+        MAX_SEARCH_DEPTH,
+    )
+    .with_threads(threads)
+    .with_multi_pv(num_lines)
+    .with_info_callback(move |info| {
+        let SearchInfo::Line(line) = info else {
+            return;
+        };
+        let mut lines = iteration_lines.lock().unwrap();
+        if line.multipv == 1 {
+            lines.clear();
+        }
+        lines.extend(super::analysis_line(&line.pv, &line.score, line.depth));
+        if line.multipv == num_lines {
+            published.lock().unwrap().lines = lines.clone();
+        }
+    });
+    let limits = SearchLimits {
+        stop: Some(stop.clone()),
+        ..SearchLimits::for_depth(MAX_SEARCH_DEPTH)
+    };
+    let searching = AtomicBool::new(true);
+    thread::scope(|scope| {
+        let watchdog = scope.spawn(|| idle.watch(stop, &searching));
+        evaluator.search_with_history(position, &PositionHistory::new(position), &limits);
+        searching.store(false, Ordering::Relaxed);
+        watchdog.thread().unpark();
+    });
+    status.lock().unwrap().running = false;
+}
@@ -0,0 +1,113 @@
+use super::analysis::{AnalysisSessions, IDLE_TIMEOUT};
+use crate::UciRequest;
+
+use std::thread;
+use std::time::Duration;
+
+// The board of a UciRequest: one character per square from a1 to h8, white pieces in lower
+// case.
+fn board(pieces: &[(usize, char)]) -> String {
+    let mut squares = vec!['.'; 64];
+    for (sq_id, piece) in pieces {
+        squares[*sq_id] = *piece;
+    }
+    squares.into_iter().collect()
+}
+
+const INITIAL_POSITION: &str = "rnbqkbnrpppppppp................................PPPPPPPPRNBQKBNR";
+
+fn request(board: String, multi_pv: u32) -> UciRequest {
+    UciRequest {
+        p_to_move: "W".to_string(),
+        board,
+        req_type: "".to_string(),
+        timeout: 0,
+        multi_pv,
+    }
+}
+
+#[test]
+fn test_analysis_runs_until_it_has_nothing_left_to_find() {
+    // Back rank mate with Ra8.
+    let sessions = AnalysisSessions::default();
+    let uci_req = request(
+        board(&[
+            (0, 'r'),
+            (6, 'k'),
+            (53, 'P'),
+            (54, 'P'),
+            (55, 'P'),
+            (62, 'K'),
+        ]),
+        1,
+    );
+    let session_id = sessions.start(&uci_req).session_id;
+    let mut status = sessions.poll(session_id).unwrap();
+    for _ in 0..500 {
+        if !status.running {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+        status = sessions.poll(session_id).unwrap();
+    }
+    assert!(!status.running);
+    assert_eq!(status.lines.len(), 1);
+    assert_eq!(status.lines[0].mv, "a1a8");
+    assert_eq!(status.lines[0].score, "mate in 1");
+
+    let status = sessions.stop(session_id).unwrap().unwrap();
+    assert_eq!(status.lines[0].mv, "a1a8");
+    assert!(sessions.poll(session_id).is_none());
+    assert!(sessions.stop(session_id).is_none());
+}
+
+#[test]
+fn test_analysis_stops_on_request() {
+    let sessions = AnalysisSessions::default();
+    let first = sessions.start(&request(INITIAL_POSITION.to_string(), 0));
+    let second = sessions.start(&request(INITIAL_POSITION.to_string(), 0));
+    assert_ne!(first.session_id, second.session_id);
+    assert!(first.running);
+
+    thread::sleep(Duration::from_millis(100));
+    let status = sessions.stop(first.session_id).unwrap().unwrap();
+    assert_eq!(status.session_id, first.session_id);
+    assert!(!status.running);
+    // The other session goes on.
+    assert!(sessions.poll(second.session_id).unwrap().running);
+    sessions.stop(second.session_id);
+}
+
+#[test]
+fn test_too_many_sessions_stop_the_oldest() {
+    let sessions = AnalysisSessions::new(2, IDLE_TIMEOUT);
+    let ids = [(); 3].map(|_| {
+        sessions
+            .start(&request(INITIAL_POSITION.to_string(), 0))
+            .session_id
+    });
+    assert!(sessions.poll(ids[0]).is_none());
+    assert!(sessions.poll(ids[1]).unwrap().running);
+    assert!(sessions.poll(ids[2]).unwrap().running);
+    sessions.stop(ids[1]);
+    sessions.stop(ids[2]);
+}
+
+#[test]
+fn test_idle_sessions_stop() {
+    let sessions = AnalysisSessions::new(4, Duration::from_millis(300));
+    let idle = sessions.start(&request(INITIAL_POSITION.to_string(), 0));
+    let polled = sessions.start(&request(INITIAL_POSITION.to_string(), 0));
+    for _ in 0..10 {
+        thread::sleep(Duration::from_millis(100));
+        assert!(sessions.poll(polled.session_id).unwrap().running);
+    }
+    // Stopped, but its lines can still be read.
+    assert!(!sessions.poll(idle.session_id).unwrap().running);
+    // Once idle again, it makes room for new sessions.
+    thread::sleep(Duration::from_millis(400));
+    let new = sessions.start(&request(INITIAL_POSITION.to_string(), 0));
+    assert!(sessions.poll(idle.session_id).is_none());
+    sessions.stop(polled.session_id);
+    sessions.stop(new.session_id);
+}
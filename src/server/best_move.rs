@@ -0,0 +1,22 @@
+use crate::{chess::position::Position, UciRequest, UciResponse};
+
+use crate::evaluation::search_evaluator::MinimaxSearchEvaluator;
+use crate::evaluation::PositionEvaluationPipeline;
+use crate::search::info::InfoScore;
+
+pub fn handle_best_move_request(uci_req: &UciRequest) -> UciResponse {
+    let position = Position::from_uci(uci_req);
+
+    let minimax_evaluator =
+        MinimaxSearchEvaluator::new(Box::new(PositionEvaluationPipeline::new_default()), 4)
+            .with_threads(super::search_threads())
+            .with_hash_size(super::REQUEST_HASH_SIZE_MB);
+
+    let result = minimax_evaluator.search_with_limits(&position, &super::search_limits(uci_req));
+    UciResponse {
+        best_moves: result.pv_to_coordinates(),
+        possible_moves: "".to_string(),
+        pos_score: super::format_score(&InfoScore::from_score(result.score)),
+        lines: vec![],
+    }
+}
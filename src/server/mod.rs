@@ -1,2 +1,70 @@
+pub mod analysis;
+pub mod best_move;
+pub mod move_safety;
+pub mod multi_pv;
 pub mod position_eval;
 pub mod possible_moves;
+
+mod analysis_test;
+
+use crate::move_gen::move_list::Move;
+use crate::search::info::InfoScore;
+use crate::search::limits::SearchLimits;
+use crate::{AnalysisLine, UciRequest};
+use std::thread;
+use std::time::Duration;
+
+// Searches as deep as |uci_req.timeout| milliseconds allow. A zero timeout means no time limit,
+// in which case the search stops at depth 4.
+pub fn search_limits(uci_req: &UciRequest) -> SearchLimits {
+    if uci_req.timeout == 0 {
+        SearchLimits::for_depth(4)
+    } else {
+        SearchLimits::for_move_time(Duration::from_millis(uci_req.timeout as u64))
+    }
+}
+
+// Lines searched when the request does not say how many.
+const DEFAULT_LINES: usize = 3;
+
+// The number of lines that |uci_req|, a "multi_pv" request or the start of an analysis
+// session, asks for.
+pub fn num_lines(uci_req: &UciRequest) -> usize {
+    match uci_req.multi_pv {
+        0 => DEFAULT_LINES,
+        lines => lines as usize,
+    }
+}
+
+// Transposition table of the searches of single requests, smaller than the default one: it is
+// built for one search and dropped with it.
+pub const REQUEST_HASH_SIZE_MB: usize = 4;
+
+// Searches run on every core: the server has the machine to itself.
+pub fn search_threads() -> usize {
+    thread::available_parallelism().map_or(1, |threads| threads.get())
+}
+
+// |score| as sent in UciResponse.pos_score: centipawns from white's point of view, or
+// "mate in N" with a negative N when black is the one mating.
+pub fn format_score(score: &InfoScore) -> String {
+    match score {
+        InfoScore::Centipawns(cp) => cp.to_string(),
+        InfoScore::MateIn(moves) => format!("mate in {}", moves),
+    }
+}
+
+// A line starting with the first move of |pv|, as sent in UciResponse.lines, or None when
+// |pv| is empty.
+pub fn analysis_line(pv: &[Move], score: &InfoScore, depth: u8) -> Option<AnalysisLine> {
+    let pv = pv
+        .iter()
+        .map(|mv| mv.to_coordinates())
+        .collect::<Vec<String>>();
+    Some(AnalysisLine {
+        mv: pv.first()?.clone(),
+        score: format_score(score),
+        pv: pv.join(" "),
+        depth,
+    })
+}
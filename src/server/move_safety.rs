@@ -0,0 +1,18 @@
+use crate::{chess::position::Position, UciRequest, UciResponse};
+
+use super::possible_moves::format_moves;
+
+// Lists, in UciResponse.possible_moves and in the same format, the legal moves that lose
+// material once the exchanges they allow on their destination are played out. The web UI
+// warns before playing them.
+pub fn handle_move_safety_request(uci_req: &UciRequest) -> UciResponse {
+    let position = Position::from_uci(uci_req);
+    let mut unsafe_moves = position.legal_continuations();
+    unsafe_moves.retain(|mv| position.see(mv) < 0);
+    UciResponse {
+        best_moves: "".to_string(),
+        possible_moves: format_moves(&unsafe_moves.to_moves_map()),
+        pos_score: "".to_string(),
+        lines: vec![],
+    }
+}
@@ -0,0 +1,42 @@
+use crate::chess::history::PositionHistory;
+use crate::{chess::position::Position, AnalysisLine, UciRequest, UciResponse};
+
+use crate::evaluation::search_evaluator::{MinimaxSearchEvaluator, SearchResult};
+use crate::evaluation::PositionEvaluationPipeline;
+use crate::search::info::InfoScore;
+
+// Searches the best moves of the position, each with its own principal variation, and returns
+// them best first in UciResponse.lines. There are fewer lines than requested when there are
+// fewer legal moves, and none in checkmate or stalemate.
+pub fn handle_multi_pv_request(uci_req: &UciRequest) -> UciResponse {
+    let eval_pipeline = PositionEvaluationPipeline::new_default();
+
+    let position = Position::from_uci(uci_req);
+    let num_lines = super::num_lines(uci_req);
+
+    let minimax_evaluator = MinimaxSearchEvaluator::new(Box::new(eval_pipeline), 4)
+        .with_threads(super::search_threads())
+        .with_hash_size(super::REQUEST_HASH_SIZE_MB)
+        .with_multi_pv(num_lines);
+    let lines = minimax_evaluator.search_lines(
+        &position,
+        &PositionHistory::new(&position),
+        &super::search_limits(uci_req),
+    );
+    UciResponse {
+        best_moves: "".to_string(),
+        possible_moves: "".to_string(),
+        pos_score: "".to_string(),
+        lines: analysis_lines(&lines),
+    }
+}
+
+// |lines|, the result of a search, as sent in UciResponse.lines.
+fn analysis_lines(lines: &[SearchResult]) -> Vec<AnalysisLine> {
+    lines
+        .iter()
+        .filter_map(|line| {
+            super::analysis_line(&line.pv, &InfoScore::from_score(line.score), line.depth)
+        })
+        .collect()
+}
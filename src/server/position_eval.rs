@@ -9,30 +9,25 @@ use crate::{
     UciRequest, UciResponse,
 };
 
-use crate::evaluation::checkmate_evaluator::CheckmateEvaluator;
-use crate::evaluation::material_evaluator::MaterialEvaluator;
-use crate::evaluation::piece_coordinate_evaluator::PieceCoordinateEvaluator;
 use crate::evaluation::search_evaluator::MinimaxSearchEvaluator;
 
 use crate::evaluation::{PositionEvaluationPipeline, PositionEvaluator};
+use crate::search::info::InfoScore;
 
 pub fn handle_position_eval_request(uci_req: &UciRequest) -> UciResponse {
-    let eval_pipeline = PositionEvaluationPipeline {
-        evaluators: vec![
-            Box::new(MaterialEvaluator::new()),
-            Box::new(PieceCoordinateEvaluator::new()),
-            Box::new(CheckmateEvaluator {}),
-        ],
-    };
+    let eval_pipeline = PositionEvaluationPipeline::new_default();
 
     let position = Position::from_uci(uci_req);
 
-    let minimax_evaluator = MinimaxSearchEvaluator::new(Box::new(eval_pipeline), 4);
+    let minimax_evaluator = MinimaxSearchEvaluator::new(Box::new(eval_pipeline), 4)
+        .with_threads(super::search_threads())
+        .with_hash_size(super::REQUEST_HASH_SIZE_MB);
 
-    let score = minimax_evaluator.evaluate(&position);
+    let result = minimax_evaluator.search_with_limits(&position, &super::search_limits(uci_req));
     UciResponse {
         best_moves: "".to_string(),
         possible_moves: "".to_string(),
-        pos_score: score.to_string(),
+        pos_score: super::format_score(&InfoScore::from_score(result.score)),
+        lines: vec![],
     }
 }
@@ -5,7 +5,7 @@ use crate::{
         bitboard::{BitArraySize, BitB64, PlayerBitboard, SpecialMoveType, FULL_BOARD},
         position::Position,
     },
-    move_gen::PieceAndMoves,
+    move_gen::{MovesMap, PieceAndMoves},
     UciRequest, UciResponse,
 };
 
@@ -35,6 +35,42 @@ pub fn sq_id_to_name(sq_id: u8) -> String {
     format!("{}{}", col_to_str(col), row_to_str(row))
 }
 
+// Moves as sent in UciResponse.possible_moves: "FROM:TO" entries separated by commas, where TO
+// is "O-O" or "O-O-O" for castling and ends in "+=" and the promoted piece for promotions.
+pub fn format_moves(moves_map: &MovesMap) -> String {
+    let mut possible_moves: String = "".to_owned();
+    for (sq_id, piece_n_moves) in moves_map.iter() {
+        let cur_piece_moves = &piece_n_moves.moves;
+        for mv in piece_n_moves.moves.iter() {
+            let mut res = "".to_owned();
+            let move_to = &match mv.sp_move_type {
+                // The client only needs the destination square for en passant captures.
+                SpecialMoveType::RegularMove
+                | SpecialMoveType::EnPassantLeft
+                | SpecialMoveType::EnPassantRight => sq_id_to_name(mv.to),
+                SpecialMoveType::ShortCastle => "O-O".to_owned(),
+                SpecialMoveType::LongCastle => "O-O-O".to_owned(),
+                SpecialMoveType::PromotionToBishop => {
+                    format!("{}+=B", sq_id_to_name(mv.to))
+                }
+                SpecialMoveType::PromotionToKnight => {
+                    format!("{}+=K", sq_id_to_name(mv.to))
+                }
+                SpecialMoveType::PromotionToRook => {
+                    format!("{}+=R", sq_id_to_name(mv.to))
+                }
+                SpecialMoveType::PromotionToQueen => {
+                    format!("{}+=Q", sq_id_to_name(mv.to))
+                }
+            };
+            possible_moves += &format!("{}:{},", sq_id_to_name(*sq_id), move_to);
+        }
+    }
+
+    possible_moves.pop();
+    possible_moves
+}
+
 pub fn handle_possible_moves_request(uci_req: &UciRequest) -> UciResponse {
     println!("possible moves request");
     println!("Received board from UCI Req");
@@ -62,44 +98,12 @@ pub fn handle_possible_moves_request(uci_req: &UciRequest) -> UciResponse {
             _ => (),
         }
     }
-    let continuations_map = position.legal_continuations();
-    let mut possible_moves: String = "".to_owned();
-    for (sq_id, piece_n_moves) in continuations_map.iter() {
-        let cur_piece_moves = &piece_n_moves.moves;
-        for mv in piece_n_moves.moves.iter() {
-            let mut res = "".to_owned();
-            let move_to = &match mv.sp_move_type {
-                SpecialMoveType::RegularMove => sq_id_to_name(mv.to),
-                SpecialMoveType::ShortCastle => "O-O".to_owned(),
-                SpecialMoveType::LongCastle => "O-O-O".to_owned(),
-                SpecialMoveType::PromotionToBishop => {
-                    format!("{}+=B", sq_id_to_name(mv.to))
-                }
-                SpecialMoveType::PromotionToKnight => {
-                    format!("{}+=K", sq_id_to_name(mv.to))
-                }
-                SpecialMoveType::PromotionToRook => {
-                    format!("{}+=R", sq_id_to_name(mv.to))
-                }
-                SpecialMoveType::PromotionToQueen => {
-                    format!("{}+=Q", sq_id_to_name(mv.to))
-                }
-                SpecialMoveType::EnPassantLeft => {
-                    todo!();
-                }
-                SpecialMoveType::EnPassantRight => {
-                    todo!();
-                }
-            };
-            possible_moves += &format!("{}:{},", sq_id_to_name(*sq_id), move_to);
-        }
-    }
-
-    possible_moves.pop();
+    let possible_moves = format_moves(&position.legal_continuations().to_moves_map());
     println!("Computed possible moves: [{}]", possible_moves);
     UciResponse {
         best_moves: "".to_string(),
         possible_moves: possible_moves,
         pos_score: "".to_string(),
+        lines: vec![],
     }
 }
@@ -0,0 +1,375 @@
+// Universal Chess Interface (UCI) protocol, spoken over stdin/stdout by the `uci` binary.
+// Not to be confused with UciRequest/UciResponse, the JSON schema of the web client.
+use crate::chess::history::PositionHistory;
+use crate::chess::position::{Position, INITIAL_POSITION_FEN};
+use crate::chess::PlayerColor;
+use crate::evaluation::search_evaluator::{MinimaxSearchEvaluator, MAX_SEARCH_THREADS};
+use crate::evaluation::PositionEvaluationPipeline;
+use crate::move_gen::move_list::{Move, MAX_MOVES};
+use crate::search::info::{InfoScore, SearchInfo};
+use crate::search::limits::{Ponder, SearchLimits, MAX_SEARCH_DEPTH};
+use crate::search::transposition_table::{TranspositionTable, DEFAULT_TT_SIZE_MB, MAX_TT_SIZE_MB};
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+const ENGINE_NAME: &str = "chess_gpt";
+const DEFAULT_DEPTH: u8 = 4;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct GoParams {
+    pub depth: Option<u8>,
+    pub movetime: Option<u64>,
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: Option<u64>,
+    pub binc: Option<u64>,
+    pub movestogo: Option<u64>,
+    pub nodes: Option<u64>,
+    pub infinite: bool,
+    pub ponder: bool,
+}
+
+impl GoParams {
+    // Parses the arguments of a `go` command. Unknown or malformed arguments are ignored, as
+    // the protocol asks engines to do.
+    pub fn parse(tokens: &[&str]) -> GoParams {
+        let mut result = GoParams::default();
+        let mut i = 0;
+        while i < tokens.len() {
+            let value = tokens
+                .get(i + 1)
+                .and_then(|value| value.parse::<u64>().ok());
+            match tokens[i] {
+                "infinite" => result.infinite = true,
+                "ponder" => result.ponder = true,
+                "depth" => {
+                    result.depth = value.map(|depth| depth.min(MAX_SEARCH_DEPTH as u64) as u8)
+                }
+                "movetime" => result.movetime = value,
+                "wtime" => result.wtime = value,
+                "btime" => result.btime = value,
+                "winc" => result.winc = value,
+                "binc" => result.binc = value,
+                "movestogo" => result.movestogo = value,
+                "nodes" => result.nodes = value,
+                _ => (),
+            }
+            i += 1;
+        }
+        result
+    }
+
+    // Limits for a search by |color|. Without any time or depth argument, the search goes to
+    // |default_depth|. When pondering, the deadlines only apply after the ponderhit.
+    pub fn search_limits(&self, color: PlayerColor, default_depth: u8) -> SearchLimits {
+        let (time, inc) = match color {
+            PlayerColor::White => (self.wtime, self.winc),
+            PlayerColor::Black => (self.btime, self.binc),
+        };
+        let mut limits = if self.infinite {
+            SearchLimits::new()
+        } else if let Some(movetime) = self.movetime {
+            SearchLimits::for_move_time(Duration::from_millis(movetime))
+        } else if let Some(time) = time {
+            SearchLimits::for_clock(
+                Duration::from_millis(time),
+                Duration::from_millis(inc.unwrap_or(0)),
+                self.movestogo.map(|movestogo| movestogo as u32),
+            )
+        } else if self.nodes.is_some() {
+            SearchLimits::new()
+        } else {
+            SearchLimits::for_depth(default_depth)
+        };
+        if let Some(depth) = self.depth {
+            limits.max_depth = depth.max(1);
+        }
+        limits.max_nodes = self.nodes;
+        if self.ponder {
+            limits.ponder = Some(Arc::new(Ponder::new()));
+        }
+        limits
+    }
+}
+
+// Parses the arguments of a `position` command: `startpos` or `fen <fields>`, optionally
+// followed by `moves <m1> <m2> ...` in coordinate notation. Returns the resulting position
+// along with the positions the moves went through.
+pub fn parse_position(tokens: &[&str]) -> Result<(Position, PositionHistory), String> {
+    let moves_idx = tokens
+        .iter()
+        .position(|token| *token == "moves")
+        .unwrap_or(tokens.len());
+    let mut position = match tokens.first() {
+        Some(&"startpos") => Position::from_fen(INITIAL_POSITION_FEN)?,
+        Some(&"fen") => Position::from_fen(&tokens[1..moves_idx].join(" "))?,
+        _ => return Err("expected 'startpos' or 'fen'".to_string()),
+    };
+    let mut history = PositionHistory::new(&position);
+    for coordinates in tokens.iter().skip(moves_idx + 1) {
+        let mv = position
+            .find_legal_move(coordinates)
+            .ok_or_else(|| format!("illegal move '{}'", coordinates))?;
+        position = position.play_move(mv);
+        history.push(&position);
+    }
+    Ok((position, history))
+}
+
+pub struct UciEngine {
+    position: Position,
+    history: PositionHistory,
+    depth: u8,
+    threads: usize,
+    multi_pv: usize,
+    transposition_table: Arc<TranspositionTable>,
+    stop: Arc<AtomicBool>,
+    // Set while the search started by `go ponder` waits for `ponderhit`.
+    ponder: Option<Arc<Ponder>>,
+    search_thread: Option<JoinHandle<()>>,
+}
+
+impl UciEngine {
+    pub fn new() -> Self {
+        let position = Position::new();
+        UciEngine {
+            position,
+            history: PositionHistory::new(&position),
+            depth: DEFAULT_DEPTH,
+            threads: 1,
+            multi_pv: 1,
+            transposition_table: Arc::new(TranspositionTable::new(DEFAULT_TT_SIZE_MB)),
+            stop: Arc::new(AtomicBool::new(false)),
+            ponder: None,
+            search_thread: None,
+        }
+    }
+
+    // Handles one line sent by the GUI. Returns false once the engine should exit.
+    pub fn handle_command(&mut self, line: &str) -> bool {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let (command, args) = match tokens.split_first() {
+            Some((command, args)) => (*command, args),
+            None => return true,
+        };
+        match command {
+            "uci" => {
+                println!("id name {} {}", ENGINE_NAME, env!("CARGO_PKG_VERSION"));
+                println!("id author the {} developers", ENGINE_NAME);
+                println!(
+                    "option name Depth type spin default {} min 1 max {}",
+                    DEFAULT_DEPTH, MAX_SEARCH_DEPTH
+                );
+                println!(
+                    "option name Hash type spin default {} min 1 max {}",
+                    DEFAULT_TT_SIZE_MB, MAX_TT_SIZE_MB
+                );
+                println!(
+                    "option name Threads type spin default 1 min 1 max {}",
+                    MAX_SEARCH_THREADS
+                );
+                println!(
+                    "option name MultiPV type spin default 1 min 1 max {}",
+                    MAX_MOVES
+                );
+                println!("option name Ponder type check default false");
+                println!("uciok");
+            }
+            "isready" => println!("readyok"),
+            "ucinewgame" => {
+                self.stop_search();
+                self.position = Position::new();
+                self.history = PositionHistory::new(&self.position);
+                self.transposition_table.clear();
+            }
+            "position" => match parse_position(args) {
+                Ok((position, history)) => {
+                    self.position = position;
+                    self.history = history;
+                }
+                Err(err) => println!("info string invalid position: {}", err),
+            },
+            "setoption" => self.set_option(args),
+            "go" => self.go(GoParams::parse(args)),
+            "ponderhit" => {
+                if let Some(ponder) = self.ponder.take() {
+                    ponder.hit();
+                }
+            }
+            "stop" => self.stop_search(),
+            "quit" => {
+                self.stop_search();
+                return false;
+            }
+            _ => (),
+        }
+        true
+    }
+
+    // setoption name <name> value <value>
+    fn set_option(&mut self, args: &[&str]) {
+        let value_idx = args
+            .iter()
+            .position(|token| *token == "value")
+            .unwrap_or(args.len());
+        let name = args.get(1..value_idx).unwrap_or(&[]).join(" ");
+        let value = args.get(value_idx + 1..).unwrap_or(&[]).join(" ");
+        match name.to_lowercase().as_str() {
+            "depth" => match value.parse::<u8>() {
+                Ok(depth) if (1..=MAX_SEARCH_DEPTH).contains(&depth) => self.depth = depth,
+                _ => println!("info string invalid Depth '{}'", value),
+            },
+            "hash" => match value.parse::<usize>() {
+                Ok(size_mb) if (1..=MAX_TT_SIZE_MB).contains(&size_mb) => {
+                    self.stop_search();
+                    self.transposition_table = Arc::new(TranspositionTable::new(size_mb));
+                }
+                _ => println!("info string invalid Hash '{}'", value),
+            },
+            "threads" => match value.parse::<usize>() {
+                Ok(threads) if (1..=MAX_SEARCH_THREADS).contains(&threads) => {
+                    self.threads = threads
+                }
+                _ => println!("info string invalid Threads '{}'", value),
+            },
+            "multipv" => match value.parse::<usize>() {
+                Ok(lines) if (1..=MAX_MOVES).contains(&lines) => self.multi_pv = lines,
+                _ => println!("info string invalid MultiPV '{}'", value),
+            },
+            // Tells whether the GUI may send `go ponder`. Nothing to prepare for it.
+            "ponder" => (),
+            _ => println!("info string unknown option '{}'", name),
+        }
+    }
+
+    fn go(&mut self, params: GoParams) {
+        self.stop_search();
+        self.stop.store(false, Ordering::SeqCst);
+
+        let position = self.position;
+        let history = self.history.clone();
+        let limits = SearchLimits {
+            stop: Some(self.stop.clone()),
+            ..params.search_limits(position.player_to_move(), self.depth)
+        };
+        let infinite = params.infinite;
+        let stop = self.stop.clone();
+        self.ponder = limits.ponder.clone();
+        let ponder = limits.ponder.clone();
+        let transposition_table = self.transposition_table.clone();
+        let (threads, multi_pv) = (self.threads, self.multi_pv);
+        self.search_thread = Some(thread::spawn(move || {
+            // The GUI waits for a best move even when the search dies.
+            let pv = panic::catch_unwind(AssertUnwindSafe(|| {
+                search_best_line(
+                    &position,
+                    &history,
+                    &limits,
+                    transposition_table,
+                    threads,
+                    multi_pv,
+                )
+            }))
+            .unwrap_or_else(|_| {
+                println!("info string search failed");
+                vec![]
+            });
+            // In infinite mode the GUI expects the best move only after it sends `stop`, and
+            // when pondering, only after `ponderhit` or `stop`.
+            let pondering = || ponder.as_ref().is_some_and(|ponder| !ponder.is_hit());
+            while (infinite || pondering()) && !stop.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(1));
+            }
+            // The second move of the line is the reply to ponder on.
+            match pv.as_slice() {
+                [] => println!("bestmove 0000"),
+                [mv] => println!("bestmove {}", mv.to_coordinates()),
+                [mv, reply, ..] => println!(
+                    "bestmove {} ponder {}",
+                    mv.to_coordinates(),
+                    reply.to_coordinates()
+                ),
+            }
+        }));
+    }
+
+    fn stop_search(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(search_thread) = self.search_thread.take() {
+            // The search thread answers by itself, even when the search fails.
+            let _ = search_thread.join();
+        }
+    }
+}
+
+impl Default for UciEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Reports the progress of the search, then returns the principal variation of the best line.
+fn search_best_line(
+    position: &Position,
+    history: &PositionHistory,
+    limits: &SearchLimits,
+    transposition_table: Arc<TranspositionTable>,
+    threads: usize,
+    multi_pv: usize,
+) -> Vec<Move> {
+    let evaluator = MinimaxSearchEvaluator::new(
+        Box::new(PositionEvaluationPipeline::new_default()),
+        limits.max_depth,
+    )
+    .with_transposition_table(transposition_table)
+    .with_threads(threads)
+    .with_multi_pv(multi_pv)
+    .with_info_callback({
+        let color = position.player_to_move();
+        move |info| println!("{}", format_info(info, color))
+    });
+    evaluator.search_with_history(position, history, limits).pv
+}
+
+// The "info" line of |info|, for a search of a position where |color| is to move. UCI scores
+// are from the point of view of the player to move.
+pub fn format_info(info: &SearchInfo, color: PlayerColor) -> String {
+    match info {
+        SearchInfo::Line(line) => {
+            let score = match line.score.relative_to(color) {
+                InfoScore::Centipawns(cp) => format!("cp {}", cp),
+                InfoScore::MateIn(moves) => format!("mate {}", moves),
+            };
+            let pv = line
+                .pv
+                .iter()
+                .map(|mv| mv.to_coordinates())
+                .collect::<Vec<String>>()
+                .join(" ");
+            format!(
+                "info depth {} seldepth {} multipv {} score {} nodes {} nps {} hashfull {} time {} pv {}",
+                line.depth,
+                line.seldepth,
+                line.multipv,
+                score,
+                line.nodes,
+                line.nps,
+                line.hashfull,
+                line.time.as_millis(),
+                pv
+            )
+        }
+        SearchInfo::CurrentMove { depth, mv, number } => format!(
+            "info depth {} currmove {} currmovenumber {}",
+            depth,
+            mv.to_coordinates(),
+            number
+        ),
+    }
+}
+
+mod uci_test;
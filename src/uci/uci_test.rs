@@ -0,0 +1,136 @@
+use super::{format_info, parse_position, GoParams};
+use crate::chess::position::{Position, INITIAL_POSITION_FEN};
+use crate::chess::PlayerColor;
+use crate::search::info::{InfoScore, LineInfo, SearchInfo};
+use crate::search::limits::MAX_SEARCH_DEPTH;
+use std::time::Duration;
+
+#[test]
+fn test_parse_position_startpos() {
+    let (pos, history) = parse_position(&["startpos"]).unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(pos.to_fen(), INITIAL_POSITION_FEN);
+
+    let (pos, history) = parse_position(&["startpos", "moves", "e2e4", "e7e5", "g1f3"]).unwrap();
+    assert_eq!(history.len(), 4);
+    assert_eq!(
+        pos.to_fen(),
+        "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+    );
+}
+
+#[test]
+fn test_parse_position_fen_with_special_moves() {
+    let tokens: Vec<&str> = "fen r3k2r/6P1/8/8/8/8/8/R3K2R w KQkq - 0 1 moves e1g1 e8c8 g7g8q"
+        .split_whitespace()
+        .collect();
+    let (pos, _) = parse_position(&tokens).unwrap();
+    assert_eq!(pos.to_fen(), "2kr2Qr/8/8/8/8/8/8/R4RK1 b - - 0 2");
+}
+
+#[test]
+fn test_parse_position_errors() {
+    assert!(parse_position(&[]).is_err());
+    assert!(parse_position(&["fen", "not", "a", "fen"]).is_err());
+    assert!(parse_position(&["startpos", "moves", "e2e5"]).is_err());
+}
+
+#[test]
+fn test_parse_position_keeps_repetitions() {
+    let (pos, history) = parse_position(&[
+        "startpos", "moves", "g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8",
+    ])
+    .unwrap();
+    assert_eq!(
+        pos.position_info.zobrist_hash,
+        Position::new().position_info.zobrist_hash
+    );
+    assert!(history.is_threefold_repetition(&pos));
+}
+
+#[test]
+fn test_parse_go() {
+    let tokens: Vec<&str> = "wtime 60000 btime 50000 winc 1000 binc 500 movestogo 10"
+        .split_whitespace()
+        .collect();
+    let params = GoParams::parse(&tokens);
+    assert_eq!(
+        params,
+        GoParams {
+            wtime: Some(60000),
+            btime: Some(50000),
+            winc: Some(1000),
+            binc: Some(500),
+            movestogo: Some(10),
+            ..GoParams::default()
+        }
+    );
+    let limits = params.search_limits(PlayerColor::White, 4);
+    assert_eq!(limits.max_depth, MAX_SEARCH_DEPTH);
+    assert!(limits.soft_deadline.unwrap() <= limits.hard_deadline.unwrap());
+
+    let limits = GoParams::parse(&["depth", "3"]).search_limits(PlayerColor::White, 4);
+    assert_eq!(limits.max_depth, 3);
+    assert!(limits.hard_deadline.is_none());
+
+    let limits = GoParams::parse(&[]).search_limits(PlayerColor::Black, 4);
+    assert_eq!(limits.max_depth, 4);
+
+    let limits = GoParams::parse(&["movetime", "250"]).search_limits(PlayerColor::Black, 4);
+    assert_eq!(limits.soft_deadline, limits.hard_deadline);
+    assert!(limits.hard_deadline.is_some());
+
+    let limits = GoParams::parse(&["nodes", "5000"]).search_limits(PlayerColor::White, 4);
+    assert_eq!(limits.max_nodes, Some(5000));
+    assert_eq!(limits.max_depth, MAX_SEARCH_DEPTH);
+
+    let params = GoParams::parse(&["infinite"]);
+    assert!(params.infinite);
+    assert!(params
+        .search_limits(PlayerColor::White, 4)
+        .hard_deadline
+        .is_none());
+
+    // Pondering on the clock: the deadlines wait for the ponderhit.
+    let params = GoParams::parse(&["ponder", "wtime", "0", "btime", "0"]);
+    assert!(params.ponder);
+    let limits = params.search_limits(PlayerColor::White, 4);
+    assert!(!limits.is_past_hard_deadline());
+    limits.ponder.as_ref().unwrap().hit();
+    assert!(limits.is_past_hard_deadline());
+}
+
+#[test]
+fn test_format_info() {
+    let position = Position::from_fen("4k3/8/8/8/8/8/3q4/3Q3K b - - 0 1").unwrap();
+    let line = LineInfo {
+        depth: 4,
+        seldepth: 9,
+        multipv: 1,
+        score: InfoScore::Centipawns(-850),
+        pv: vec![position.find_legal_move("d2d1").unwrap()],
+        nodes: 12345,
+        nps: 246900,
+        time: Duration::from_millis(50),
+        hashfull: 3,
+    };
+    // Scores are from the point of view of the player to move.
+    assert_eq!(
+        format_info(&SearchInfo::Line(line.clone()), PlayerColor::Black),
+        "info depth 4 seldepth 9 multipv 1 score cp 850 nodes 12345 nps 246900 hashfull 3 time 50 pv d2d1"
+    );
+    let mate = LineInfo {
+        score: InfoScore::MateIn(-2),
+        ..line
+    };
+    assert!(format_info(&SearchInfo::Line(mate), PlayerColor::Black).contains("score mate 2 "));
+    let current_move = SearchInfo::CurrentMove {
+        depth: 7,
+        mv: position.find_legal_move("d2d1").unwrap(),
+        number: 3,
+    };
+    assert_eq!(
+        format_info(&current_move, PlayerColor::Black),
+        "info depth 7 currmove d2d1 currmovenumber 3"
+    );
+}